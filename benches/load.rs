@@ -0,0 +1,38 @@
+// Loading time of the bundled country file, run with `cargo bench`
+use cty_rs::Cty;
+use std::{fs, hint::black_box, time::Instant};
+
+const ROUNDS: u32 = 50;
+
+fn main() {
+    let data = fs::read_to_string("cty.dat").expect("cty.dat in the crate root");
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        black_box(data.parse::<Cty>().unwrap());
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+    let cty = data.parse::<Cty>().unwrap();
+    println!(
+        "load: {:.2} ms for {} ({} bytes)",
+        elapsed.as_secs_f64() * 1000.0,
+        cty.stats(),
+        data.len()
+    );
+
+    let calls = [
+        "DL1ABC",
+        "W1ABC/KH6",
+        "UA3XYZ/0",
+        "KG4AB",
+        "VP2E/K1AA",
+        "JA1XYZ",
+    ];
+    let start = Instant::now();
+    for _ in 0..100_000 {
+        for call in calls {
+            black_box(cty.lookup(black_box(call)));
+        }
+    }
+    let elapsed = start.elapsed() / (100_000 * calls.len() as u32);
+    println!("lookup: {} ns", elapsed.as_nanos());
+}
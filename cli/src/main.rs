@@ -0,0 +1,361 @@
+use clap::{Parser, Subcommand, ValueEnum};
+use cty_rs::{
+    adif::Adif, geo, lint, maidenhead, Cty, CtyBuilder, Diff, Entity, ParseError, ParseErrors,
+    Prefix,
+};
+use serde::Serialize;
+use std::{error::Error, fs, process::ExitCode};
+
+#[cfg(feature = "serve")]
+mod serve;
+
+#[derive(Parser)]
+#[command(name = "cty", about = "Amateur radio country file lookups")]
+struct Cli {
+    /// Country file to load
+    #[arg(
+        long,
+        short,
+        global = true,
+        env = "CTY_FILE",
+        default_value = "cty.dat"
+    )]
+    file: String,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Resolve callsigns to their DXCC entity
+    Lookup {
+        #[arg(required = true)]
+        callsigns: Vec<String>,
+        /// Print one JSON object per callsign
+        #[arg(long)]
+        json: bool,
+        /// Home QTH as a Maidenhead locator or "lat,lon" (east positive) for beam headings
+        #[arg(long, env = "CTY_HOME")]
+        home: Option<String>,
+    },
+    /// Show step by step how callsigns are resolved
+    Explain {
+        #[arg(required = true)]
+        callsigns: Vec<String>,
+    },
+    /// Check a country file for malformed records and suspicious data
+    Validate {
+        /// Country file to check, defaults to --file
+        path: Option<String>,
+    },
+    /// Show what changed between two versions of a country file
+    Diff { old: String, new: String },
+    /// Fill in and correct the DXCC, zone, continent and country fields of an ADIF log
+    Adif {
+        input: String,
+        /// Write the corrected log here instead of standard output
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    /// Write the parsed database in another format
+    Export {
+        #[arg(long, value_enum, default_value_t = Format::Json)]
+        format: Format,
+        /// Write to this file instead of standard output
+        #[arg(long, short)]
+        output: Option<String>,
+    },
+    /// Answer GET /lookup/<call> and GET /entities with JSON over HTTP
+    #[cfg(feature = "serve")]
+    Serve {
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+        /// Address to listen on, e.g. 0.0.0.0 for every interface
+        #[arg(long, default_value_t = std::net::IpAddr::from([127, 0, 0, 1]))]
+        bind: std::net::IpAddr,
+        /// Reload the country file this often, in minutes
+        #[arg(long)]
+        refresh: Option<u64>,
+        /// Download the country file from here instead of reading --file
+        #[arg(long)]
+        url: Option<String>,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Format {
+    /// Entities and prefixes with their overrides
+    Json,
+    /// Entity locations as a GeoJSON FeatureCollection
+    Geojson,
+}
+
+#[derive(Serialize)]
+struct LookupResult<'a> {
+    callsign: &'a str,
+    entity: &'a Entity,
+    grid: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    path: Option<geo::Path>,
+}
+
+fn parse_home(home: &str) -> Result<(f64, f64), Box<dyn Error>> {
+    match home.split_once(',') {
+        Some((lat, lon)) => Ok((lat.trim().parse()?, lon.trim().parse()?)),
+        None => maidenhead::from_locator(home.trim()),
+    }
+}
+
+fn format_coordinates(entity: &Entity) -> String {
+    let (lat, lon) = entity.coordinates();
+    format!(
+        "{:.2}{} {:.2}{}",
+        lat.abs(),
+        if lat < 0.0 { 'S' } else { 'N' },
+        lon.abs(),
+        if lon < 0.0 { 'W' } else { 'E' }
+    )
+}
+
+fn print_result(result: &LookupResult) {
+    let entity = result.entity;
+    println!("{}: {} ({})", result.callsign, entity.name, entity.prefix);
+    println!(
+        "  CQ zone {}, ITU zone {}, {}, DXCC {}",
+        entity.cq, entity.itu, entity.continent, entity.dxcc
+    );
+    println!(
+        "  {} ({}), UTC{}",
+        format_coordinates(entity),
+        result.grid,
+        entity.timezone
+    );
+    if let Some(path) = result.path {
+        println!(
+            "  Heading {:.0}° {:.0} km, long path {:.0}° {:.0} km",
+            path.bearing, path.distance, path.long_bearing, path.long_distance
+        );
+    }
+}
+
+fn lookup(
+    cty: &Cty,
+    callsigns: &[String],
+    json: bool,
+    home: Option<&str>,
+) -> Result<ExitCode, Box<dyn Error>> {
+    let home = home.map(parse_home).transpose()?;
+    let mut status = ExitCode::SUCCESS;
+    for callsign in callsigns {
+        let callsign = callsign.to_ascii_uppercase();
+        let Some(entity) = cty.lookup(&callsign) else {
+            let suggestions = cty
+                .suggest(&callsign, 3)
+                .into_iter()
+                .map(|(prefix, _)| prefix.pattern.as_str())
+                .collect::<Vec<_>>();
+            match suggestions.is_empty() {
+                true => eprintln!("{callsign}: not found"),
+                false => eprintln!(
+                    "{callsign}: not found, did you mean {}?",
+                    suggestions.join(", ")
+                ),
+            }
+            status = ExitCode::FAILURE;
+            continue;
+        };
+        let path = home.map(|(lat, lon)| {
+            let (to_lat, to_lon) = entity.coordinates();
+            geo::path(lat, lon, to_lat, to_lon)
+        });
+        let result = LookupResult {
+            callsign: &callsign,
+            entity,
+            grid: entity.grid(),
+            path,
+        };
+        if json {
+            println!("{}", serde_json::to_string(&result)?);
+        } else {
+            print_result(&result);
+        }
+    }
+    Ok(status)
+}
+
+fn validate(path: &str) -> Result<ExitCode, Box<dyn Error>> {
+    // Report every malformed record before the checks that need a parsed file
+    if let Err(err) = CtyBuilder::new().all_errors(true).load(path) {
+        let errors = err.downcast::<ParseErrors>()?;
+        for err in &errors.errors {
+            println!("{path}:{}: {}", err.line, err.message);
+        }
+        return Ok(ExitCode::FAILURE);
+    }
+    let diagnostics = match lint::lint_file(path) {
+        Ok(diagnostics) => diagnostics,
+        Err(err) => match err.downcast::<ParseError>() {
+            Ok(err) => {
+                println!("{path}:{}: {}", err.line, err.message);
+                return Ok(ExitCode::FAILURE);
+            }
+            Err(err) => return Err(err),
+        },
+    };
+    for diagnostic in &diagnostics {
+        println!("{path}:{}: {}", diagnostic.line, diagnostic.message);
+    }
+    Ok(if diagnostics.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+fn describe_prefix(prefix: &Prefix) -> String {
+    let entity = prefix.resolved();
+    format!(
+        "{}{} {} (CQ {}, ITU {})",
+        if prefix.is_exact { "=" } else { "" },
+        prefix.pattern,
+        entity.name,
+        entity.cq,
+        entity.itu
+    )
+}
+
+fn print_diff(diff: &Diff) {
+    println!(
+        "{} entities added, {} removed, {} changed",
+        diff.added_entities.len(),
+        diff.removed_entities.len(),
+        diff.changed_entities.len()
+    );
+    println!(
+        "{} prefixes added, {} removed, {} changed",
+        diff.added_prefixes.len(),
+        diff.removed_prefixes.len(),
+        diff.changed_prefixes.len()
+    );
+    for entity in &diff.added_entities {
+        println!("+ {} ({})", entity.name, entity.prefix);
+    }
+    for entity in &diff.removed_entities {
+        println!("- {} ({})", entity.name, entity.prefix);
+    }
+    for (old, new) in &diff.changed_entities {
+        println!(
+            "~ {} ({}): CQ {} -> {}, ITU {} -> {}, {} -> {}",
+            new.name, new.prefix, old.cq, new.cq, old.itu, new.itu, old.continent, new.continent
+        );
+    }
+    for prefix in &diff.added_prefixes {
+        println!("+ {}", describe_prefix(prefix));
+    }
+    for prefix in &diff.removed_prefixes {
+        println!("- {}", describe_prefix(prefix));
+    }
+    for (old, new) in &diff.changed_prefixes {
+        println!("~ {} -> {}", describe_prefix(old), describe_prefix(new));
+    }
+}
+
+fn enrich(cty: &Cty, input: &str, output: Option<&str>) -> Result<ExitCode, Box<dyn Error>> {
+    let mut adif = Adif::parse(&fs::read_to_string(input)?)?;
+    let enrichment = cty.enrich_adif(&mut adif);
+    for correction in &enrichment.corrections {
+        eprintln!("{input}: {correction}");
+    }
+    for &record in &enrichment.unresolved {
+        let call = adif.records[record].get("CALL").unwrap_or_default();
+        eprintln!("{input}: QSO {} ({call}): not found", record + 1);
+    }
+    match output {
+        Some(output) => fs::write(output, adif.to_string())?,
+        None => print!("{adif}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn export(cty: &Cty, format: Format, output: Option<&str>) -> Result<ExitCode, Box<dyn Error>> {
+    let data = match format {
+        Format::Json => cty.to_json(),
+        Format::Geojson => cty.to_geojson(),
+    };
+    match output {
+        Some(output) => fs::write(output, data)?,
+        None => println!("{data}"),
+    }
+    Ok(ExitCode::SUCCESS)
+}
+
+fn run(cli: Cli) -> Result<ExitCode, Box<dyn Error>> {
+    match cli.command {
+        Command::Lookup {
+            callsigns,
+            json,
+            home,
+        } => {
+            let cty = Cty::new(&cli.file)?;
+            lookup(&cty, &callsigns, json, home.as_deref())
+        }
+        Command::Explain { callsigns } => {
+            let cty = Cty::new(&cli.file)?;
+            for callsign in callsigns {
+                println!("{}", cty.explain(&callsign.to_ascii_uppercase()));
+            }
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Validate { path } => validate(path.as_deref().unwrap_or(&cli.file)),
+        Command::Diff { old, new } => {
+            let old = Cty::new(&old)?;
+            let new = Cty::new(&new)?;
+            print_diff(&old.diff(&new));
+            Ok(ExitCode::SUCCESS)
+        }
+        Command::Adif { input, output } => {
+            let cty = Cty::new(&cli.file)?;
+            enrich(&cty, &input, output.as_deref())
+        }
+        Command::Export { format, output } => {
+            let cty = Cty::new(&cli.file)?;
+            export(&cty, format, output.as_deref())
+        }
+        #[cfg(feature = "serve")]
+        Command::Serve {
+            port,
+            bind,
+            refresh,
+            url,
+        } => {
+            let refresh = refresh.map(|minutes| std::time::Duration::from_secs(minutes * 60));
+            let address = std::net::SocketAddr::new(bind, port);
+            serve::serve(&cli.file, url.as_deref(), address, refresh)?;
+            Ok(ExitCode::SUCCESS)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(status) => status,
+        Err(err) => {
+            eprintln!("cty: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn home_qth() {
+        assert_eq!(parse_home("51.5, -0.1").unwrap(), (51.5, -0.1));
+        assert_eq!(parse_home("JO51").unwrap(), (51.5, 11.0));
+        assert!(parse_home("nowhere").is_err());
+        assert!(parse_home("1O51").is_err());
+        assert!(parse_home("JO51!!").is_err());
+    }
+}
@@ -0,0 +1,237 @@
+// Tiny HTTP/1.1 server answering lookups from a shared database
+use cty_rs::{Cty, CtyService, Entity};
+use serde::Serialize;
+use std::{
+    error::Error,
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+// Most bytes read of a request line and its headers
+const MAX_HEAD: u64 = 8192;
+// Connections handled at once, further ones wait to be accepted
+const WORKERS: usize = 8;
+
+#[derive(Serialize)]
+struct Lookup<'a> {
+    callsign: &'a str,
+    entity: &'a Entity,
+    grid: String,
+}
+
+#[derive(Serialize)]
+struct Failure<'a> {
+    error: &'a str,
+}
+
+// Decode %XX escapes, e.g. W1AW%2FKH6
+fn percent_decode(path: &str) -> Option<String> {
+    let bytes = path.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes.get(i + 1..i + 3)?;
+                if !hex.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                out.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                i += 3;
+            }
+            b => {
+                out.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(out).ok()
+}
+
+fn error(status: u16, message: &str) -> (u16, String) {
+    let body = serde_json::to_string(&Failure { error: message });
+    (status, body.unwrap_or_default())
+}
+
+// Status and JSON body for a request
+pub fn respond(cty: &Cty, method: &str, target: &str) -> (u16, String) {
+    if method != "GET" {
+        return error(405, "Only GET is supported");
+    }
+    let path = target.split(['?', '#']).next().unwrap_or_default();
+    if path == "/entities" {
+        let entities = cty.entities().collect::<Vec<_>>();
+        return (200, serde_json::to_string(&entities).unwrap_or_default());
+    }
+    let Some(call) = path.strip_prefix("/lookup/") else {
+        return error(404, "Not found");
+    };
+    let Some(call) = percent_decode(call).filter(|call| !call.is_empty()) else {
+        return error(400, "Invalid callsign");
+    };
+    let callsign = call.to_ascii_uppercase();
+    match cty.lookup(&callsign) {
+        Some(entity) => {
+            let lookup = Lookup {
+                callsign: &callsign,
+                entity,
+                grid: entity.grid(),
+            };
+            (200, serde_json::to_string(&lookup).unwrap_or_default())
+        }
+        None => error(404, &format!("{callsign} not found")),
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        431 => "Request Header Fields Too Large",
+        _ => "Error",
+    }
+}
+
+// The request line, after reading up to the blank line ending the headers.
+// Headers aren't needed, but are read so the client sees a clean close.
+// None if the input ends first, e.g. at the `MAX_HEAD` limit.
+fn read_head(mut reader: impl BufRead) -> Result<Option<String>, Box<dyn Error>> {
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    if !request.ends_with('\n') {
+        return Ok(None);
+    }
+    let mut header = String::new();
+    loop {
+        header.clear();
+        reader.read_line(&mut header)?;
+        match header.as_str() {
+            "\n" | "\r\n" => return Ok(Some(request)),
+            line if !line.ends_with('\n') => return Ok(None),
+            _ => {}
+        }
+    }
+}
+
+fn handle(service: &CtyService, stream: TcpStream) -> Result<(), Box<dyn Error>> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    stream.set_write_timeout(Some(Duration::from_secs(10)))?;
+    let (status, body) = match read_head(BufReader::new((&stream).take(MAX_HEAD)))? {
+        Some(request) => {
+            let mut parts = request.split_whitespace();
+            match (parts.next(), parts.next()) {
+                (Some(method), Some(target)) => respond(&service.current(), method, target),
+                _ => error(400, "Malformed request"),
+            }
+        }
+        None => error(431, "Request too large or incomplete"),
+    };
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason(status),
+        body.len()
+    )?;
+    Ok(())
+}
+
+// Serve until the process is stopped, reloading the database every `refresh`
+// from `url` if given and otherwise from `file`
+pub fn serve(
+    file: &str,
+    url: Option<&str>,
+    address: SocketAddr,
+    refresh: Option<Duration>,
+) -> Result<(), Box<dyn Error>> {
+    let (file, url) = (file.to_string(), url.map(str::to_string));
+    let service = CtyService::with_loader(
+        move || match &url {
+            Some(url) => Cty::load_url(url),
+            None => Cty::new(&file),
+        },
+        refresh,
+    )?;
+    let listener = TcpListener::bind(address)?;
+    eprintln!("cty: serving on {address}");
+    // Accepting blocks while all workers are busy and the queue is full
+    let (sender, receiver) = mpsc::sync_channel::<TcpStream>(WORKERS);
+    let receiver = Arc::new(Mutex::new(receiver));
+    for _ in 0..WORKERS {
+        let (service, receiver) = (service.clone(), receiver.clone());
+        thread::spawn(move || loop {
+            // The lock is only held while waiting for the next connection
+            let next = receiver.lock().ok().and_then(|r| r.recv().ok());
+            let Some(stream) = next else {
+                return;
+            };
+            if let Err(err) = handle(&service, stream) {
+                eprintln!("cty: {err}");
+            }
+        });
+    }
+    for stream in listener.incoming().flatten() {
+        sender.send(stream)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn routes() {
+        let cty = Cty::new("../cty.dat").unwrap();
+        let (status, body) = respond(&cty, "GET", "/lookup/w1aw%2Fkh6");
+        assert_eq!(status, 200);
+        let lookup: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(lookup["callsign"], "W1AW/KH6");
+        assert_eq!(lookup["entity"]["name"], "Hawaii");
+        assert_eq!(respond(&cty, "GET", "/lookup/DL1ABC/P").0, 200);
+
+        let (status, body) = respond(&cty, "GET", "/entities?pretty");
+        assert_eq!(status, 200);
+        let entities: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(entities.len(), 346);
+
+        assert_eq!(
+            respond(&cty, "GET", "/lookup/QQ1ABC"),
+            (404, r#"{"error":"QQ1ABC not found"}"#.to_string())
+        );
+        assert_eq!(respond(&cty, "GET", "/lookup/").0, 400);
+        assert_eq!(respond(&cty, "GET", "/lookup/%ZZ").0, 400);
+        assert_eq!(respond(&cty, "GET", "/lookup/%+1").0, 400);
+        assert_eq!(respond(&cty, "GET", "/lookup/%4").0, 400);
+        assert_eq!(
+            respond(&cty, "GET", "/lookup/A%C3%84%2F1"),
+            (404, r#"{"error":"AÄ/1 not found"}"#.to_string())
+        );
+        assert_eq!(respond(&cty, "GET", "/").0, 404);
+        assert_eq!(respond(&cty, "POST", "/entities").0, 405);
+    }
+
+    #[test]
+    fn request_head() {
+        let head = |input: &[u8]| read_head(BufReader::new(input.take(MAX_HEAD))).unwrap();
+        let request = "GET /entities HTTP/1.1\r\nHost: localhost\r\n\r\n";
+        assert_eq!(
+            head(request.as_bytes()).unwrap(),
+            "GET /entities HTTP/1.1\r\n"
+        );
+        assert!(head(b"GET / HTTP/1.1\nHost: localhost\n\n").is_some());
+        assert!(head(b"GET / HTTP/1.1\n\n").is_some());
+        assert!(head(b"GET / HTTP/1.1\r\nHost: localhost\r\n").is_none());
+        assert!(head(b"GET / HTTP/1.1").is_none());
+        assert!(head(&[b'A'; 2 * MAX_HEAD as usize]).is_none());
+        let mut long = b"GET / HTTP/1.1\r\n".to_vec();
+        long.extend(std::iter::repeat_n(b"X: y\r\n", 2000).flatten());
+        long.extend(b"\r\n");
+        assert!(head(&long).is_none());
+    }
+}
@@ -0,0 +1,57 @@
+#ifndef CTY_H
+#define CTY_H
+
+/* Generated by cbindgen from src/cty_ffi.rs, do not edit */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+typedef struct CtyDatabase CtyDatabase;
+
+typedef struct {
+  const char *name;
+  uint32_t cq;
+  uint32_t itu;
+  const char *continent;
+  float lat;
+  float lon;
+  int32_t utc_offset;
+  const char *prefix;
+  bool waedc;
+  uint32_t dxcc;
+} CtyEntity;
+
+
+
+/**
+ * Load a country file, returning NULL if it can't be read or parsed.
+ *
+ * # Safety
+ *
+ * `filename` must be a NUL terminated string.
+ */
+CtyDatabase *cty_load(const char *filename);
+
+/**
+ * Look up a callsign, filling `out` and returning true if it was found.
+ *
+ * # Safety
+ *
+ * `db` must come from `cty_load`, `callsign` must be a NUL terminated string
+ * and `out` must point to a writable `CtyEntity`.
+ */
+bool cty_lookup(const CtyDatabase *db, const char *callsign, CtyEntity *out);
+
+/**
+ * Free a database, invalidating the strings of entities looked up in it.
+ *
+ * # Safety
+ *
+ * `db` must come from `cty_load` and not be used afterwards. NULL is ignored.
+ */
+void cty_free(CtyDatabase *db);
+
+#endif  /* CTY_H */
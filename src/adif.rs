@@ -0,0 +1,236 @@
+// Reading ADIF (.adi) logs and filling in their DXCC fields from the database
+use crate::{Cty, Entity};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{error::Error, fmt};
+
+// A field of a QSO record, e.g. <CALL:6>DL1ABC
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    // Data type indicator, e.g. the N of <CQZ:2:N>14
+    pub kind: Option<String>,
+    pub value: String,
+}
+
+// A QSO, fields in file order
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Record {
+    pub fields: Vec<Field>,
+}
+
+impl Record {
+    // Field names are case-insensitive
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|f| f.name.eq_ignore_ascii_case(name))
+            .map(|f| f.value.as_str())
+    }
+    // Replace the value of a field, adding it if missing
+    pub fn set(&mut self, name: &str, value: &str) {
+        match self
+            .fields
+            .iter_mut()
+            .find(|f| f.name.eq_ignore_ascii_case(name))
+        {
+            Some(field) => field.value = value.to_string(),
+            None => self.fields.push(Field {
+                name: name.to_string(),
+                kind: None,
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Adif {
+    // Everything up to and including <EOH>, written back unchanged
+    pub header: String,
+    pub records: Vec<Record>,
+}
+
+// Field written with a different value than the log had
+#[derive(Debug, Clone, PartialEq)]
+pub struct Correction {
+    // Index of the record in `Adif::records`
+    pub record: usize,
+    pub call: String,
+    pub field: &'static str,
+    // None if the field was missing
+    pub old: Option<String>,
+    pub new: String,
+}
+
+impl fmt::Display for Correction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.old {
+            Some(old) => write!(
+                f,
+                "QSO {} ({}): {} {} -> {}",
+                self.record + 1,
+                self.call,
+                self.field,
+                old,
+                self.new
+            ),
+            None => write!(
+                f,
+                "QSO {} ({}): {} set to {}",
+                self.record + 1,
+                self.call,
+                self.field,
+                self.new
+            ),
+        }
+    }
+}
+
+// Result of `Cty::enrich_adif`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Enrichment {
+    pub corrections: Vec<Correction>,
+    // Indexes of records without a CALL or with one the database doesn't resolve
+    pub unresolved: Vec<usize>,
+}
+
+impl Adif {
+    pub fn parse(data: &str) -> Result<Adif, Box<dyn Error>> {
+        let mut adif = Adif::default();
+        let mut record = Record::default();
+        let mut rest = data;
+        // A file not starting with a field always has a header
+        match data.to_ascii_uppercase().find("<EOH>") {
+            Some(eoh) => {
+                adif.header = data[..eoh + 5].to_string();
+                rest = &data[eoh + 5..];
+            }
+            None if !data.trim_start().starts_with('<') => {
+                return Err("Header is missing <EOH>".into())
+            }
+            None => {}
+        }
+        while let Some(start) = rest.find('<') {
+            let Some(len) = rest[start..].find('>') else {
+                return Err(format!("Unterminated field {}", &rest[start..]).into());
+            };
+            let end = start + len;
+            let spec = &rest[start + 1..end];
+            let mut parts = spec.split(':');
+            let name = parts.next().unwrap_or_default();
+            if name.eq_ignore_ascii_case("EOR") {
+                adif.records.push(core::mem::take(&mut record));
+                rest = &rest[end + 1..];
+                continue;
+            }
+            let length = match parts.next() {
+                Some(length) => length
+                    .parse::<usize>()
+                    .map_err(|_| format!("Invalid length in <{spec}>"))?,
+                None => return Err(format!("Missing length in <{spec}>").into()),
+            };
+            let kind = parts.next().map(str::to_string);
+            // The length counts characters, not bytes
+            let data_start = end + 1;
+            let value_end = rest[data_start..]
+                .char_indices()
+                .nth(length)
+                .map_or(rest.len(), |(i, _)| data_start + i);
+            if rest[data_start..value_end].chars().count() < length {
+                return Err(format!("Field <{spec}> runs past the end of the file").into());
+            }
+            record.fields.push(Field {
+                name: name.to_string(),
+                kind,
+                value: rest[data_start..value_end].to_string(),
+            });
+            rest = &rest[value_end..];
+        }
+        if !record.fields.is_empty() {
+            return Err("Last record is missing <EOR>".into());
+        }
+        Ok(adif)
+    }
+}
+
+impl fmt::Display for Adif {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.header.is_empty() {
+            writeln!(f, "{}", self.header)?;
+        }
+        for record in &self.records {
+            for field in &record.fields {
+                write!(f, "<{}:{}", field.name, field.value.chars().count())?;
+                if let Some(kind) = &field.kind {
+                    write!(f, ":{kind}")?;
+                }
+                write!(f, ">{} ", field.value)?;
+            }
+            writeln!(f, "<EOR>")?;
+        }
+        Ok(())
+    }
+}
+
+impl Cty {
+    // Name of the DXCC entity, WAE-only entities like Sicily count for their
+    // parent. Used for entities missing from the ADIF names.
+    fn dxcc_name<'a>(&'a self, entity: &'a Entity) -> &'a str {
+        if !entity.waedc {
+            return &entity.name;
+        }
+        self.entity_by_dxcc(entity.dxcc)
+            .map_or(&entity.name, |e| &e.name)
+    }
+    // Resolve the CALL of every record and fill in or correct its DXCC, CQZ,
+    // ITUZ, CONT and COUNTRY fields
+    pub fn enrich_adif(&self, adif: &mut Adif) -> Enrichment {
+        let mut enrichment = Enrichment::default();
+        for (i, record) in adif.records.iter_mut().enumerate() {
+            let Some(call) = record.get("CALL").map(|c| c.trim().to_ascii_uppercase()) else {
+                enrichment.unresolved.push(i);
+                continue;
+            };
+            let Some(entity) = self.lookup(&call) else {
+                enrichment.unresolved.push(i);
+                continue;
+            };
+            let mut fields = Vec::new();
+            if entity.dxcc != 0 {
+                fields.push(("DXCC", entity.dxcc.to_string()));
+            }
+            fields.push(("CQZ", entity.cq.to_string()));
+            fields.push(("ITUZ", entity.itu.to_string()));
+            fields.push(("CONT", entity.continent.to_string()));
+            let country = entity.adif_country_name();
+            let country = country.unwrap_or_else(|| self.dxcc_name(entity));
+            fields.push(("COUNTRY", country.to_string()));
+            for (field, new) in fields {
+                let old = record.get(field);
+                // Numbers may be written with leading zeros
+                let same = old.is_some_and(|old| {
+                    let old = old.trim();
+                    old.eq_ignore_ascii_case(&new)
+                        || matches!((old.parse::<u32>(), new.parse::<u32>()), (Ok(a), Ok(b)) if a == b)
+                });
+                if same {
+                    continue;
+                }
+                enrichment.corrections.push(Correction {
+                    record: i,
+                    call: call.clone(),
+                    field,
+                    old: old.map(str::to_string),
+                    new: new.clone(),
+                });
+                record.set(field, &new);
+            }
+        }
+        enrichment
+    }
+}
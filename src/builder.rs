@@ -0,0 +1,157 @@
+use crate::{Cty, ParseWarning};
+#[cfg(feature = "fs")]
+use alloc::string::{String, ToString};
+use alloc::{boxed::Box, vec::Vec};
+use core::error::Error;
+
+// Sign convention of the longitudes in the loaded files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Longitude {
+    // Positive west, as in cty.dat
+    #[default]
+    West,
+    // Positive east, converted on load so `Entity::lon` stays positive west
+    East,
+}
+
+// How lookups treat calls with a portable designator, e.g. W1ABC/KH6
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PortableCalls {
+    // Where the station operates: the designator's entity, or the home
+    // call moved to the designated call area
+    #[default]
+    Location,
+    // Always the entity of the home call
+    HomeCall,
+    // The longest prefix of the call as written, slashes included
+    Prefix,
+}
+
+// Load-time configuration of a `Cty`
+#[derive(Debug, Clone, Default)]
+pub struct CtyBuilder {
+    pub(crate) lenient: bool,
+    pub(crate) all_errors: bool,
+    pub(crate) longitude: Longitude,
+    pub(crate) normalize_calls: bool,
+    pub(crate) portable_calls: PortableCalls,
+    #[cfg(feature = "http")]
+    pub(crate) http_timeout: Option<std::time::Duration>,
+    #[cfg(feature = "http")]
+    pub(crate) max_download_size: Option<u64>,
+    #[cfg(feature = "fs")]
+    pub(crate) files: Vec<String>,
+    #[cfg(feature = "exceptions")]
+    pub(crate) exceptions: Vec<String>,
+}
+
+impl CtyBuilder {
+    pub fn new() -> CtyBuilder {
+        CtyBuilder::default()
+    }
+
+    // Skip malformed records instead of failing the whole load
+    pub fn lenient(mut self, lenient: bool) -> CtyBuilder {
+        self.lenient = lenient;
+        self
+    }
+
+    // Keep parsing past malformed records and fail with a `ParseErrors`
+    // listing all of them, unless lenient parsing skips them anyway
+    pub fn all_errors(mut self, all_errors: bool) -> CtyBuilder {
+        self.all_errors = all_errors;
+        self
+    }
+
+    // Longitude convention of the files, for ones not in cty.dat format
+    pub fn longitude(mut self, longitude: Longitude) -> CtyBuilder {
+        self.longitude = longitude;
+        self
+    }
+
+    // Trim and uppercase callsigns before looking them up
+    pub fn normalize_calls(mut self, normalize: bool) -> CtyBuilder {
+        self.normalize_calls = normalize;
+        self
+    }
+
+    pub fn portable_calls(mut self, portable: PortableCalls) -> CtyBuilder {
+        self.portable_calls = portable;
+        self
+    }
+
+    // Add a file to layer on top of the previous ones, prefix by prefix
+    #[cfg(feature = "fs")]
+    pub fn with_file(mut self, filename: &str) -> CtyBuilder {
+        self.files.push(filename.to_string());
+        self
+    }
+
+    // Add a TOML or JSON exception list, applied after all the country files
+    #[cfg(feature = "exceptions")]
+    pub fn with_exceptions(mut self, filename: &str) -> CtyBuilder {
+        self.exceptions.push(filename.to_string());
+        self
+    }
+
+    // Parse the contents of a country file, without touching the filesystem
+    pub fn parse(&self, data: &str) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        Cty::from_lines(crate::str_lines(data), self)
+    }
+
+    // Parse a country file that may have a byte order mark or, with the
+    // encoding feature, be in another encoding than UTF-8
+    pub fn parse_bytes(&self, data: &[u8]) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        self.parse(&crate::encoding::decode(data)?)
+    }
+
+    // Load a country file with the added files on top, returning the records
+    // skipped by lenient parsing. The warnings are always empty in strict mode,
+    // which fails on the first error.
+    #[cfg(feature = "fs")]
+    pub fn load(&self, filename: &str) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        self.layer(self.parse(&crate::read_file(filename)?)?)
+    }
+
+    // Put the added files and exception lists on top of a parsed file
+    #[cfg(feature = "fs")]
+    pub(crate) fn layer(
+        &self,
+        parsed: (Cty, Vec<ParseWarning>),
+    ) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        self.layer_with(parsed, |file| self.parse(&crate::read_file(file)?))
+    }
+
+    // Like `layer`, parsing the added files with `parse_file`
+    #[cfg(feature = "fs")]
+    pub(crate) fn layer_with(
+        &self,
+        (mut cty, mut warnings): (Cty, Vec<ParseWarning>),
+        parse_file: impl Fn(&str) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>>,
+    ) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        for file in &self.files {
+            let (overlay, overlay_warnings) = parse_file(file)?;
+            cty.merge(overlay);
+            warnings.extend(overlay_warnings);
+        }
+        #[cfg(feature = "exceptions")]
+        for file in &self.exceptions {
+            let exceptions = crate::Exceptions::load(file)?;
+            cty.apply_exceptions_with(&exceptions, self.longitude)?;
+        }
+        Ok((cty, warnings))
+    }
+
+    // Load the added files, later ones overriding earlier ones
+    #[cfg(feature = "fs")]
+    pub fn build(&self) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        let Some((first, rest)) = self.files.split_first() else {
+            return Err("No country file added".into());
+        };
+        CtyBuilder {
+            files: rest.to_vec(),
+            ..self.clone()
+        }
+        .load(first)
+    }
+}
@@ -0,0 +1,155 @@
+// Reading Cabrillo contest logs and counting the multipliers they worked
+use crate::{Cty, MultiplierTracker};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::error::Error;
+
+// Bands in frequency order, as (name, lowest kHz, highest kHz, Cabrillo designator)
+const BANDS: [(&str, u32, u32, &str); 16] = [
+    ("160m", 1800, 2000, ""),
+    ("80m", 3500, 4000, ""),
+    ("60m", 5060, 5450, ""),
+    ("40m", 7000, 7300, ""),
+    ("30m", 10100, 10150, ""),
+    ("20m", 14000, 14350, ""),
+    ("17m", 18068, 18168, ""),
+    ("15m", 21000, 21450, ""),
+    ("12m", 24890, 24990, ""),
+    ("10m", 28000, 29700, ""),
+    ("6m", 50000, 54000, "50"),
+    ("4m", 70000, 71000, "70"),
+    ("2m", 144000, 148000, "144"),
+    ("1.25m", 222000, 225000, "222"),
+    ("70cm", 420000, 450000, "432"),
+    ("23cm", 1240000, 1300000, "1.2G"),
+];
+
+// Band of a QSO line's frequency field, kHz or a VHF designator like 144
+fn band(frequency: &str) -> String {
+    let band = match frequency.parse::<u32>() {
+        Ok(khz) => BANDS
+            .iter()
+            .find(|b| (b.1..=b.2).contains(&khz) || b.3 == frequency),
+        Err(_) => BANDS.iter().find(|b| b.3.eq_ignore_ascii_case(frequency)),
+    };
+    band.map_or_else(|| frequency.to_string(), |b| b.0.to_string())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Qso {
+    // As written in the log, kHz on HF
+    pub frequency: String,
+    pub band: String,
+    pub mode: String,
+    pub date: String,
+    pub time: String,
+    pub sent_call: String,
+    // Exchange after the sent call, e.g. 599 14
+    pub sent: Vec<String>,
+    // The station worked
+    pub call: String,
+    pub received: Vec<String>,
+    // Transmitter ID of multi-transmitter logs
+    pub transmitter: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Log {
+    // Tags other than QSO in file order, e.g. ("CONTEST", "CQ-WW-CW")
+    pub headers: Vec<(String, String)>,
+    pub qsos: Vec<Qso>,
+}
+
+pub use crate::Multipliers;
+
+impl Log {
+    pub fn parse(data: &str) -> Result<Log, Box<dyn Error>> {
+        let mut log = Log::default();
+        for (i, line) in data.lines().enumerate() {
+            let Some((tag, value)) = line.split_once(':') else {
+                if line.trim().is_empty() {
+                    continue;
+                }
+                return Err(format!("line {}: Expected TAG: value", i + 1).into());
+            };
+            let tag = tag.trim().to_ascii_uppercase();
+            let value = value.trim();
+            if tag == "QSO" {
+                let qso = parse_qso(value).map_err(|err| format!("line {}: {err}", i + 1))?;
+                log.qsos.push(qso);
+            } else {
+                log.headers.push((tag, value.to_string()));
+            }
+        }
+        Ok(log)
+    }
+    // First value of a header tag, e.g. CALLSIGN
+    pub fn header(&self, tag: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(t, _)| t.eq_ignore_ascii_case(tag))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+// freq mode date time sent-call sent-exch... call rcvd-exch... [transmitter],
+// the sent and received halves have the same number of fields
+fn parse_qso(value: &str) -> Result<Qso, String> {
+    let fields = value.split_whitespace().collect::<Vec<_>>();
+    if fields.len() < 6 {
+        return Err(format!(
+            "Expected at least 6 QSO fields, found {}",
+            fields.len()
+        ));
+    }
+    let exchange = &fields[4..];
+    let (exchange, transmitter) = match exchange.len() % 2 {
+        0 => (exchange, None),
+        _ => (
+            &exchange[..exchange.len() - 1],
+            exchange.last().map(|t| t.to_string()),
+        ),
+    };
+    let (sent, received) = exchange.split_at(exchange.len() / 2);
+    let strings = |fields: &[&str]| fields.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+    Ok(Qso {
+        frequency: fields[0].to_string(),
+        band: band(fields[0]),
+        mode: fields[1].to_string(),
+        date: fields[2].to_string(),
+        time: fields[3].to_string(),
+        sent_call: sent[0].to_ascii_uppercase(),
+        sent: strings(&sent[1..]),
+        call: received[0].to_ascii_uppercase(),
+        received: strings(&received[1..]),
+        transmitter,
+    })
+}
+
+impl Cty {
+    // Unique entities, zones and continents worked per band, bands in frequency order
+    pub fn multipliers(&self, log: &Log) -> Vec<Multipliers> {
+        let mut tracker = MultiplierTracker::new();
+        for qso in &log.qsos {
+            match self.lookup(&qso.call) {
+                Some(entity) => {
+                    tracker.add(&qso.band, entity);
+                }
+                None => tracker.add_unresolved(&qso.band, &qso.call),
+            }
+        }
+        let mut bands = tracker.into_bands();
+        let rank = |band: &str| {
+            BANDS
+                .iter()
+                .position(|b| b.0 == band)
+                .unwrap_or(BANDS.len())
+        };
+        bands.sort_by_key(|b| rank(&b.band));
+        bands
+    }
+}
@@ -0,0 +1,234 @@
+// Decomposition of callsigns into the home call and the operating location
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{error::Error, fmt, str::FromStr};
+
+// Suffixes that say how a station operates, not where
+const MODIFIERS: [&str; 5] = ["P", "M", "MM", "AM", "QRP"];
+
+// A callsign split at its slashes, e.g. VP2E/K1AA/P
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Parts<'a> {
+    // The home call, K1AA
+    pub base: &'a str,
+    // Where the station operates from, VP2E, or a call area number like the 7 of K1AA/7
+    pub location: Option<&'a str>,
+}
+
+impl Parts<'_> {
+    // Portable designator that is only a call area number
+    pub fn call_area(&self) -> Option<&str> {
+        self.location
+            .filter(|l| l.bytes().all(|b| b.is_ascii_digit()))
+    }
+}
+
+// The modifiers of a callsign, e.g. P of DL1ABC/P
+pub(crate) fn modifiers(callsign: &str) -> impl Iterator<Item = &str> {
+    callsign.split('/').filter(|p| MODIFIERS.contains(p))
+}
+
+pub(crate) fn split(callsign: &str) -> Parts<'_> {
+    let parts = callsign
+        .split('/')
+        .filter(|p| !p.is_empty() && !MODIFIERS.contains(p))
+        .collect::<Vec<_>>();
+    match parts[..] {
+        [] => Parts {
+            base: callsign,
+            location: None,
+        },
+        [base] => Parts {
+            base,
+            location: None,
+        },
+        // The home call is the longer part, designators usually come first
+        [first, second, ..] if second.len() >= first.len() => Parts {
+            base: second,
+            location: Some(first),
+        },
+        [first, second, ..] => Parts {
+            base: first,
+            location: Some(second),
+        },
+    }
+}
+
+// Prefix of a call without slashes: everything up to the last digit before
+// the suffix, with a 0 added to the first two letters of calls without digits
+fn home_prefix(call: &str) -> String {
+    let prefix = call.trim_end_matches(|c: char| !c.is_ascii_digit());
+    if prefix.is_empty() {
+        let end = call.char_indices().nth(2).map_or(call.len(), |(i, _)| i);
+        format!("{}0", &call[..end])
+    } else {
+        String::from(prefix)
+    }
+}
+
+// The call as issued in another call area, UA0XYZ for UA3XYZ/0. Calls
+// without a digit are returned unchanged.
+pub(crate) fn relocate(call: &str, area: &str) -> String {
+    let prefix = home_prefix(call);
+    match prefix.rfind(|c: char| c.is_ascii_digit()) {
+        Some(digit) if call.starts_with(&prefix) => {
+            let digits = prefix[..=digit].trim_end_matches(|c: char| c.is_ascii_digit());
+            format!("{digits}{area}{}", &call[prefix.len()..])
+        }
+        _ => String::from(call),
+    }
+}
+
+// The prefix of a callsign under the CQ WPX contest rules, e.g. DL1 of DL1ABC,
+// VP2E of VP2E/K1AA and K7 of K1AA/7. Portable designators without a number
+// get a 0 added, /P, /M, /MM, /AM and /QRP are ignored. Calls with
+// characters other than ASCII have no prefix.
+pub fn wpx_prefix(callsign: &str) -> Option<String> {
+    let callsign = callsign.trim().to_ascii_uppercase();
+    let parts = split(&callsign);
+    if !callsign.is_ascii() || !parts.base.bytes().any(|b| b.is_ascii_alphabetic()) {
+        return None;
+    }
+    Some(match (parts.call_area(), parts.location) {
+        (Some(area), _) => {
+            let prefix = home_prefix(parts.base);
+            format!(
+                "{}{area}",
+                prefix.trim_end_matches(|c: char| c.is_ascii_digit())
+            )
+        }
+        (None, Some(location)) if location.bytes().any(|b| b.is_ascii_digit()) => {
+            String::from(location)
+        }
+        (None, Some(location)) => format!("{location}0"),
+        (None, None) => home_prefix(parts.base),
+    })
+}
+
+// Home call of the form prefix, digits, suffix: 1 to 3 characters with a
+// letter, up to 4 digits and 1 to 4 letters, e.g. DL 1 ABC, 3DA 0 XYZ, GB 13 COL
+fn plausible_base(call: &str) -> bool {
+    let suffix = call.len()
+        - call
+            .trim_end_matches(|c: char| c.is_ascii_uppercase())
+            .len();
+    let rest = &call[..call.len() - suffix];
+    let digits = rest.len() - rest.trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let prefix = &rest[..rest.len() - digits];
+    (1..=4).contains(&suffix)
+        && (1..=4).contains(&digits)
+        && (1..=3).contains(&prefix.len())
+        && prefix.bytes().any(|b| b.is_ascii_uppercase())
+}
+
+// Portable designator: a call area digit or a prefix like KH6 or VP2E
+fn plausible_designator(designator: &str) -> bool {
+    designator.len() == 1 && designator.bytes().all(|b| b.is_ascii_digit())
+        || (1..=4).contains(&designator.len()) && designator.bytes().any(|b| b.is_ascii_uppercase())
+}
+
+// Whether a string is shaped like an amateur callsign, without consulting
+// any database: rejects spotting noise like CQ, TEST or 599 before a lookup
+pub fn is_plausible_callsign(callsign: &str) -> bool {
+    let callsign = callsign.trim().to_ascii_uppercase();
+    if !callsign
+        .bytes()
+        .all(|b| b.is_ascii_uppercase() || b.is_ascii_digit() || b == b'/')
+        || callsign.split('/').any(str::is_empty)
+    {
+        return false;
+    }
+    let locations = callsign
+        .split('/')
+        .filter(|p| !MODIFIERS.contains(p))
+        .count();
+    let parts = split(&callsign);
+    locations <= 2 && plausible_base(parts.base) && parts.location.is_none_or(plausible_designator)
+}
+
+// A callsign taken apart, e.g. EA8/DL1ABC/P into the designator EA8, the
+// home call DL1ABC and the suffix P. Parsing uppercases the call and rejects
+// ones `is_plausible_callsign` rejects.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Callsign {
+    pub prefix_designator: Option<String>,
+    pub base_call: String,
+    // Everything after the home call in order: designators like KH6 or a
+    // call area digit, and modifiers like P and MM
+    pub suffixes: Vec<String>,
+}
+
+impl Callsign {
+    pub fn parse(callsign: &str) -> Result<Callsign, Box<dyn Error>> {
+        if !is_plausible_callsign(callsign) {
+            return Err(format!("{callsign} is not a callsign").into());
+        }
+        let callsign = callsign.trim().to_ascii_uppercase();
+        let base = split(&callsign).base;
+        let mut parts = callsign.split('/');
+        let prefix_designator = match parts.next() {
+            Some(first) if first != base => Some(first.to_string()),
+            _ => None,
+        };
+        // The home call is the first part or follows the designator
+        if prefix_designator.is_some() {
+            parts.next();
+        }
+        Ok(Callsign {
+            prefix_designator,
+            base_call: base.to_string(),
+            suffixes: parts.map(str::to_string).collect(),
+        })
+    }
+    // Where the station operates from, if not its home call area: the
+    // designator before or after the home call
+    pub fn location(&self) -> Option<&str> {
+        self.prefix_designator.as_deref().or_else(|| {
+            self.suffixes
+                .iter()
+                .map(String::as_str)
+                .find(|s| !MODIFIERS.contains(s))
+        })
+    }
+    // Suffixes that say how the station operates, e.g. P and QRP
+    pub fn modifiers(&self) -> impl Iterator<Item = &str> {
+        self.suffixes
+            .iter()
+            .map(String::as_str)
+            .filter(|s| MODIFIERS.contains(s))
+    }
+    pub fn is_portable(&self) -> bool {
+        self.prefix_designator.is_some() || !self.suffixes.is_empty()
+    }
+    // See `wpx_prefix`
+    pub fn wpx_prefix(&self) -> Option<String> {
+        wpx_prefix(&self.to_string())
+    }
+}
+
+impl FromStr for Callsign {
+    type Err = Box<dyn Error>;
+
+    fn from_str(callsign: &str) -> Result<Callsign, Box<dyn Error>> {
+        Callsign::parse(callsign)
+    }
+}
+
+// The parts joined by slashes again
+impl fmt::Display for Callsign {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(designator) = &self.prefix_designator {
+            write!(f, "{designator}/")?;
+        }
+        write!(f, "{}", self.base_call)?;
+        for suffix in &self.suffixes {
+            write!(f, "/{suffix}")?;
+        }
+        Ok(())
+    }
+}
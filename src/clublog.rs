@@ -0,0 +1,66 @@
+// Clublog most-wanted list, as served by mostwanted.php (JSON object of
+// rank to prefix) or exported as CSV lines of rank,prefix[,name].
+use crate::{Cty, Entity};
+use std::{collections::HashMap, error::Error};
+
+#[derive(Debug, Clone, Default)]
+pub struct MostWanted {
+    // Rank (1 is most wanted) and Clublog prefix
+    pub ranks: Vec<(u32, String)>,
+}
+
+impl MostWanted {
+    pub fn parse(data: &str) -> Result<MostWanted, Box<dyn Error>> {
+        let data = data.trim_start();
+        let mut ranks = if data.starts_with('{') {
+            let map: HashMap<String, String> = serde_json::from_str(data)?;
+            map.into_iter()
+                .map(|(rank, prefix)| Ok((rank.trim().parse::<u32>()?, prefix)))
+                .collect::<Result<Vec<_>, Box<dyn Error>>>()?
+        } else {
+            let mut ranks = Vec::new();
+            for line in data.lines().map(str::trim).filter(|l| !l.is_empty()) {
+                let mut fields = line.split(',').map(|f| f.trim().trim_matches('"'));
+                let (Some(rank), Some(prefix)) = (fields.next(), fields.next()) else {
+                    return Err(format!("Invalid most wanted line {line}").into());
+                };
+                match rank.parse::<u32>() {
+                    Ok(rank) => ranks.push((rank, prefix.to_string())),
+                    // Header line
+                    Err(_) if ranks.is_empty() => continue,
+                    Err(err) => return Err(err.into()),
+                }
+            }
+            ranks
+        };
+        ranks.sort();
+        Ok(MostWanted { ranks })
+    }
+}
+
+impl Entity {
+    pub fn most_wanted_rank(&self) -> Option<u32> {
+        self.most_wanted
+    }
+}
+
+impl Cty {
+    #[cfg(feature = "fs")]
+    pub fn load_most_wanted(&mut self, filename: &str) -> Result<(), Box<dyn Error>> {
+        let list = MostWanted::parse(&std::fs::read_to_string(filename)?)?;
+        self.apply_most_wanted(&list);
+        Ok(())
+    }
+    // Set the rank of every entity whose primary prefix matches a list entry,
+    // entities missing from the list get no rank
+    pub fn apply_most_wanted(&mut self, list: &MostWanted) {
+        let ranks = list
+            .ranks
+            .iter()
+            .map(|(rank, prefix)| (prefix.to_ascii_uppercase(), *rank))
+            .collect::<HashMap<_, _>>();
+        self.update_entities(|entity| {
+            entity.most_wanted = ranks.get(&entity.prefix.to_ascii_uppercase()).copied();
+        });
+    }
+}
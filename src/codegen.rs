@@ -0,0 +1,90 @@
+// Turning a country file into Rust source for a `StaticCty`, e.g. from a
+// build script:
+//
+//     cty_rs::codegen::write("cty.dat", out_dir.join("cty.rs"), "CTY")?;
+//
+// and in the crate, `include!(concat!(env!("OUT_DIR"), "/cty.rs"));` defines
+// `pub static CTY: cty_rs::StaticCty`.
+use crate::{Cty, Prefix};
+use std::{error::Error, fmt::Write, path::Path};
+
+fn prefix_literal(name: &str, prefix: &Prefix) -> String {
+    let entity = prefix.resolved();
+    format!(
+        "cty_rs::StaticPrefix {{ pattern: {:?}, exact: {}, entity: &{name}_ENTITIES[{}], \
+         cq: {}, itu: {}, continent: {:?}, lat: {:?}, lon: {:?}, utc_offset: {} }}",
+        prefix.pattern,
+        prefix.is_exact,
+        prefix.entity_index,
+        entity.cq,
+        entity.itu,
+        entity.continent.as_str(),
+        entity.lat,
+        entity.lon,
+        entity.utc_offset()
+    )
+}
+
+// Source defining `pub static <name>: cty_rs::StaticCty` and the arrays it
+// refers to. Custom rules can't be generated and are left out.
+pub fn generate(cty: &Cty, name: &str) -> String {
+    let mut out = String::new();
+    let version = cty.version();
+    // Writing to a String can't fail
+    let _ = writeln!(
+        out,
+        "// Generated by cty_rs::codegen from release {}, do not edit",
+        version.unwrap_or("unknown")
+    );
+    let _ = writeln!(
+        out,
+        "static {name}_ENTITIES: [cty_rs::StaticEntity; {}] = [",
+        cty.entities.len()
+    );
+    for entity in &cty.entities {
+        let _ = writeln!(
+            out,
+            "    cty_rs::StaticEntity {{ name: {:?}, prefix: {:?}, dxcc: {}, id: {}, waedc: {}, deleted: {} }},",
+            entity.name, entity.prefix, entity.dxcc, entity.id.0, entity.waedc, entity.deleted
+        );
+    }
+    let _ = writeln!(out, "];");
+
+    let order = cty.prefixes.sorted();
+    let _ = writeln!(
+        out,
+        "static {name}_PREFIXES: [cty_rs::StaticPrefix; {}] = [",
+        order.len()
+    );
+    for &i in &order {
+        let _ = writeln!(out, "    {},", prefix_literal(name, &cty.prefixes[i]));
+    }
+    let _ = writeln!(out, "];");
+
+    let _ = writeln!(
+        out,
+        "pub static {name}: cty_rs::StaticCty = cty_rs::StaticCty {{"
+    );
+    let _ = writeln!(out, "    version: {version:?},");
+    let _ = writeln!(out, "    entities: &{name}_ENTITIES,");
+    let _ = writeln!(out, "    prefixes: &{name}_PREFIXES,");
+    let _ = writeln!(out, "    rules: &[");
+    for (pattern, target) in cty.rules.iter().filter_map(|rule| rule.as_pattern()) {
+        let position = order
+            .iter()
+            .position(|&i| cty.prefixes[i].pattern == target && !cty.prefixes[i].is_exact);
+        if let Some(position) = position {
+            let _ = writeln!(out, "        ({pattern:?}, &{name}_PREFIXES[{position}]),");
+        }
+    }
+    let _ = writeln!(out, "    ],");
+    let _ = writeln!(out, "}};");
+    out
+}
+
+// Load a country file and write the source for it
+pub fn write<P: AsRef<Path>>(filename: &str, out: P, name: &str) -> Result<(), Box<dyn Error>> {
+    let cty = Cty::new(filename)?;
+    std::fs::write(out, generate(&cty, name))?;
+    Ok(())
+}
@@ -0,0 +1,167 @@
+// Arrow record batches of the entity and prefix tables and of batch lookups,
+// for joining contest logs against the country data in Pandas or Polars.
+// Coordinates are in degrees with east longitude positive, UTC offsets in
+// seconds east.
+use crate::{Cty, Entity};
+use alloc::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
+use arrow_array::{
+    ArrayRef, BooleanArray, Float32Array, Int32Array, RecordBatch, StringArray, UInt32Array,
+    UInt8Array,
+};
+use arrow_schema::{DataType, Field, Schema};
+use core::error::Error;
+
+// The columns describing an entity, null where a row has none
+fn entity_columns(entities: &[Option<&Entity>], nullable: bool) -> Vec<(Field, ArrayRef)> {
+    let column = |name: &str, data_type: DataType, array: ArrayRef| {
+        (Field::new(name, data_type, nullable), array)
+    };
+    let each = || entities.iter();
+    Vec::from([
+        column(
+            "prefix",
+            DataType::Utf8,
+            Arc::new(StringArray::from_iter(
+                each().map(|e| e.map(|e| e.prefix.as_str())),
+            )),
+        ),
+        column(
+            "name",
+            DataType::Utf8,
+            Arc::new(StringArray::from_iter(
+                each().map(|e| e.map(|e| e.name.as_str())),
+            )),
+        ),
+        column(
+            "dxcc",
+            DataType::UInt32,
+            Arc::new(UInt32Array::from_iter(each().map(|e| e.map(|e| e.dxcc)))),
+        ),
+        column(
+            "cq",
+            DataType::UInt8,
+            Arc::new(UInt8Array::from_iter(each().map(|e| e.map(|e| e.cq.get())))),
+        ),
+        column(
+            "itu",
+            DataType::UInt8,
+            Arc::new(UInt8Array::from_iter(
+                each().map(|e| e.map(|e| e.itu.get())),
+            )),
+        ),
+        column(
+            "continent",
+            DataType::Utf8,
+            Arc::new(StringArray::from_iter(
+                each().map(|e| e.map(|e| e.continent.to_string())),
+            )),
+        ),
+        column(
+            "lat",
+            DataType::Float32,
+            Arc::new(Float32Array::from_iter(each().map(|e| e.map(|e| e.lat)))),
+        ),
+        column(
+            "lon",
+            DataType::Float32,
+            Arc::new(Float32Array::from_iter(each().map(|e| e.map(|e| -e.lon)))),
+        ),
+        column(
+            "utc_offset",
+            DataType::Int32,
+            Arc::new(Int32Array::from_iter(
+                each().map(|e| e.map(Entity::utc_offset)),
+            )),
+        ),
+        column(
+            "waedc",
+            DataType::Boolean,
+            Arc::new(BooleanArray::from_iter(each().map(|e| e.map(|e| e.waedc)))),
+        ),
+        column(
+            "deleted",
+            DataType::Boolean,
+            Arc::new(BooleanArray::from_iter(
+                each().map(|e| e.map(|e| e.deleted)),
+            )),
+        ),
+    ])
+}
+
+fn batch(columns: Vec<(Field, ArrayRef)>) -> Result<RecordBatch, Box<dyn Error>> {
+    let (fields, arrays): (Vec<_>, Vec<_>) = columns.into_iter().unzip();
+    Ok(RecordBatch::try_new(Arc::new(Schema::new(fields)), arrays)?)
+}
+
+impl Cty {
+    // One row per entity in file order: prefix, name, dxcc, cq, itu,
+    // continent, lat, lon, utc_offset, waedc and deleted
+    pub fn entities_batch(&self) -> Result<RecordBatch, Box<dyn Error>> {
+        let entities = self.entities().map(Some).collect::<Vec<_>>();
+        let mut columns = entity_columns(&entities, false);
+        columns.push((
+            Field::new("most_wanted", DataType::UInt32, true),
+            Arc::new(UInt32Array::from_iter(
+                entities.iter().flatten().map(|e| e.most_wanted),
+            )),
+        ));
+        batch(columns)
+    }
+    // One row per prefix and exact call in pattern order: pattern, exact and
+    // the entity columns with the prefix's overrides applied, the prefix
+    // column being the entity's primary prefix to join on
+    pub fn prefixes_batch(&self) -> Result<RecordBatch, Box<dyn Error>> {
+        let prefixes = self.prefixes_sorted().collect::<Vec<_>>();
+        let entities = prefixes
+            .iter()
+            .map(|p| Some(p.resolved.as_ref()))
+            .collect::<Vec<_>>();
+        let mut columns = Vec::from([
+            (
+                Field::new("pattern", DataType::Utf8, false),
+                Arc::new(StringArray::from_iter_values(
+                    prefixes.iter().map(|p| p.pattern.as_str()),
+                )) as ArrayRef,
+            ),
+            (
+                Field::new("exact", DataType::Boolean, false),
+                Arc::new(BooleanArray::from_iter(
+                    prefixes.iter().map(|p| Some(p.is_exact)),
+                )),
+            ),
+        ]);
+        columns.extend(entity_columns(&entities, false));
+        batch(columns)
+    }
+    // One row per call as given with the entity it resolves to, and nulls in
+    // the entity columns for calls that don't resolve
+    pub fn lookups_batch<'a, I>(&self, calls: I) -> Result<RecordBatch, Box<dyn Error>>
+    where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let calls = calls.into_iter().collect::<Vec<_>>();
+        let entities = calls
+            .iter()
+            .map(|call| self.lookup(call))
+            .collect::<Vec<_>>();
+        let mut columns = Vec::from([(
+            Field::new("call", DataType::Utf8, false),
+            Arc::new(StringArray::from_iter_values(calls.iter().copied())) as ArrayRef,
+        )]);
+        columns.extend(entity_columns(&entities, true));
+        batch(columns)
+    }
+}
+
+// Write a batch as a Parquet file, e.g. `Cty::entities_batch` to
+// File::create("entities.parquet")
+#[cfg(feature = "parquet")]
+pub fn write_parquet<W>(batch: &RecordBatch, writer: W) -> Result<(), Box<dyn Error>>
+where
+    W: std::io::Write + Send,
+{
+    let mut writer = parquet::arrow::ArrowWriter::try_new(writer, batch.schema(), None)?;
+    writer.write(batch)?;
+    writer.close()?;
+    Ok(())
+}
@@ -0,0 +1,157 @@
+// Country files compressed with gzip or in a zip archive like the bundles of
+// country-files.com, recognized by their first bytes whatever the file is
+// called
+use flate2::{
+    read::{DeflateDecoder, MultiGzDecoder},
+    Crc,
+};
+use std::{
+    error::Error,
+    io::{BufRead, BufReader, Cursor, Read},
+};
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+const END_OF_DIRECTORY: &[u8] = b"PK\x05\x06";
+const DIRECTORY_ENTRY: &[u8] = b"PK\x01\x02";
+
+// Largest size a local file may decompress to, far more than any country file
+pub(crate) const MAX_SIZE: u64 = 64 * 1024 * 1024;
+
+fn u16_at(data: &[u8], at: usize) -> Option<usize> {
+    let bytes = data.get(at..at + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+fn u32_at(data: &[u8], at: usize) -> Option<u32> {
+    let bytes = data.get(at..at + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+// A file in the central directory of a zip archive
+struct ZipEntry<'a> {
+    name: &'a str,
+    method: usize,
+    flags: usize,
+    crc: u32,
+    compressed_size: usize,
+    header_offset: usize,
+}
+
+fn zip_entries(archive: &[u8]) -> Option<Vec<ZipEntry<'_>>> {
+    // The end of directory record is followed by a comment of up to 64 kB
+    let search = archive.len().saturating_sub(22 + 0xffff);
+    let end = archive[search..]
+        .windows(4)
+        .rposition(|w| w == END_OF_DIRECTORY)?
+        + search;
+    let count = u16_at(archive, end + 10)?;
+    let mut at = u32_at(archive, end + 16)? as usize;
+    let mut entries = Vec::with_capacity(count);
+    for _ in 0..count {
+        if archive.get(at..at + 4)? != DIRECTORY_ENTRY {
+            return None;
+        }
+        let name_len = u16_at(archive, at + 28)?;
+        let name = archive.get(at + 46..at + 46 + name_len)?;
+        entries.push(ZipEntry {
+            name: std::str::from_utf8(name).ok()?,
+            flags: u16_at(archive, at + 8)?,
+            method: u16_at(archive, at + 10)?,
+            crc: u32_at(archive, at + 16)?,
+            compressed_size: u32_at(archive, at + 20)? as usize,
+            header_offset: u32_at(archive, at + 42)? as usize,
+        });
+        at += 46 + name_len + u16_at(archive, at + 30)? + u16_at(archive, at + 32)?;
+    }
+    Some(entries)
+}
+
+// The contents of an entry, failing if they are larger than `limit` bytes
+fn extract(archive: &[u8], entry: &ZipEntry, limit: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    let invalid = || format!("Invalid zip entry {}", entry.name);
+    if entry.flags & 1 != 0 {
+        return Err(format!("Zip entry {} is encrypted", entry.name).into());
+    }
+    let at = entry.header_offset;
+    let data_start = at + 30 + u16_at(archive, at + 26).ok_or_else(invalid)?;
+    let data_start = data_start + u16_at(archive, at + 28).ok_or_else(invalid)?;
+    let data = archive
+        .get(data_start..data_start + entry.compressed_size)
+        .ok_or_else(invalid)?;
+    let mut contents = Vec::new();
+    match entry.method {
+        0 => data.take(limit + 1).read_to_end(&mut contents)?,
+        8 => DeflateDecoder::new(data)
+            .take(limit + 1)
+            .read_to_end(&mut contents)?,
+        method => {
+            return Err(format!("Zip entry {} uses compression method {method}", entry.name).into())
+        }
+    };
+    if contents.len() as u64 > limit {
+        return Err(format!("Zip entry {} larger than {limit} bytes", entry.name).into());
+    }
+    let mut crc = Crc::new();
+    crc.update(&contents);
+    if crc.sum() != entry.crc {
+        return Err(format!("Checksum mismatch in zip entry {}", entry.name).into());
+    }
+    Ok(contents)
+}
+
+// Name of an entry without its directories
+fn file_name<'a>(entry: &ZipEntry<'a>) -> &'a str {
+    entry.name.rsplit('/').next().unwrap_or_default()
+}
+
+// The country file of a zip archive, cty.dat if there is one and otherwise
+// the first .dat file
+fn unzip(archive: &[u8], limit: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    let entries = zip_entries(archive).ok_or("Invalid zip archive")?;
+    let entry = entries
+        .iter()
+        .find(|entry| file_name(entry).eq_ignore_ascii_case("cty.dat"))
+        .or_else(|| {
+            entries
+                .iter()
+                .find(|entry| file_name(entry).to_ascii_lowercase().ends_with(".dat"))
+        })
+        .ok_or("No .dat file in zip archive")?;
+    extract(archive, entry, limit)
+}
+
+// The contents of a possibly compressed file as it is read, ending after
+// `limit` + 1 bytes so callers can tell a file larger than `limit`. Zip
+// archives are read completely first, their directory is at the end, and
+// fail right away if their country file is larger.
+pub(crate) fn reader<'a>(
+    input: impl Read + 'a,
+    limit: u64,
+) -> Result<Box<dyn Read + 'a>, Box<dyn Error>> {
+    let mut input = BufReader::new(input);
+    let start = input.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(MultiGzDecoder::new(input).take(limit + 1)))
+    } else if start.starts_with(ZIP_MAGIC) {
+        let mut archive = Vec::new();
+        input.read_to_end(&mut archive)?;
+        Ok(Box::new(Cursor::new(unzip(&archive, limit)?)))
+    } else {
+        Ok(Box::new(input))
+    }
+}
+
+// Decompress a file read into memory, failing if it expands to more than
+// `limit` bytes. Other files are returned as they are.
+pub(crate) fn decompress(data: Vec<u8>, limit: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    if !data.starts_with(GZIP_MAGIC) && !data.starts_with(ZIP_MAGIC) {
+        return Ok(data);
+    }
+    let mut contents = Vec::new();
+    reader(&data[..], limit)?.read_to_end(&mut contents)?;
+    if contents.len() as u64 > limit {
+        return Err(format!("File larger than {limit} bytes when decompressed").into());
+    }
+    Ok(contents)
+}
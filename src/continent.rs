@@ -0,0 +1,75 @@
+use alloc::{boxed::Box, format, string::String};
+use core::{error::Error, fmt, str::FromStr};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Continent {
+    NA,
+    SA,
+    EU,
+    AF,
+    AS,
+    OC,
+    AN,
+    // Kept as written by lenient parsing, strict parsing rejects it
+    Unknown(String),
+}
+
+impl Continent {
+    pub const ALL: [Continent; 7] = [
+        Continent::NA,
+        Continent::SA,
+        Continent::EU,
+        Continent::AF,
+        Continent::AS,
+        Continent::OC,
+        Continent::AN,
+    ];
+
+    // The two letter abbreviation used by cty.dat
+    pub fn as_str(&self) -> &str {
+        match self {
+            Continent::NA => "NA",
+            Continent::SA => "SA",
+            Continent::EU => "EU",
+            Continent::AF => "AF",
+            Continent::AS => "AS",
+            Continent::OC => "OC",
+            Continent::AN => "AN",
+            Continent::Unknown(s) => s,
+        }
+    }
+
+    // Parse an abbreviation, keeping unknown ones instead of failing
+    pub fn parse_lenient(s: &str) -> Continent {
+        s.parse().unwrap_or_else(|_| Continent::Unknown(s.into()))
+    }
+
+    pub fn is_known(&self) -> bool {
+        !matches!(self, Continent::Unknown(_))
+    }
+}
+
+impl FromStr for Continent {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Continent::ALL
+            .into_iter()
+            .find(|c| c.as_str().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Invalid continent {s}").into())
+    }
+}
+
+impl fmt::Display for Continent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// Serialized as the abbreviation, e.g. "EU"
+#[cfg(feature = "serde")]
+impl serde::Serialize for Continent {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
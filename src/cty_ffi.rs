@@ -0,0 +1,133 @@
+// C interface, see include/cty.h
+use crate::{Cty, Entity, Prefix};
+use std::{
+    collections::HashMap,
+    ffi::{c_char, CStr, CString},
+    ptr,
+};
+
+// Entity as seen from C. The strings stay valid until the database is freed.
+#[repr(C)]
+pub struct CtyEntity {
+    pub name: *const c_char,
+    pub cq: u32,
+    pub itu: u32,
+    pub continent: *const c_char,
+    pub lat: f32,
+    // Positive west, as in the country file
+    pub lon: f32,
+    // Seconds east of UTC
+    pub utc_offset: i32,
+    pub prefix: *const c_char,
+    pub waedc: bool,
+    pub dxcc: u32,
+}
+
+// NUL terminated copies of an entity's strings
+struct EntityStrings {
+    name: CString,
+    continent: CString,
+    prefix: CString,
+}
+
+// Opaque database handle
+pub struct CtyDatabase {
+    cty: Cty,
+    // Keyed by the address of the resolved entity, prefixes with overrides
+    // resolve to entities of their own
+    strings: HashMap<*const Entity, EntityStrings>,
+}
+
+fn c_string(s: &str) -> CString {
+    // Country files don't contain NUL bytes, drop them if one does
+    CString::new(s.replace('\0', "")).unwrap_or_default()
+}
+
+impl CtyDatabase {
+    fn new(cty: Cty) -> CtyDatabase {
+        let mut strings = HashMap::new();
+        // Rules resolve some calls to the entities themselves
+        let entities = cty.prefixes().map(Prefix::resolved).chain(cty.entities());
+        for entity in entities {
+            strings
+                .entry(entity as *const Entity)
+                .or_insert_with(|| EntityStrings {
+                    name: c_string(&entity.name),
+                    continent: c_string(entity.continent.as_str()),
+                    prefix: c_string(&entity.prefix),
+                });
+        }
+        CtyDatabase { cty, strings }
+    }
+}
+
+/// Load a country file, returning NULL if it can't be read or parsed.
+///
+/// # Safety
+///
+/// `filename` must be a NUL terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn cty_load(filename: *const c_char) -> *mut CtyDatabase {
+    if filename.is_null() {
+        return ptr::null_mut();
+    }
+    let Ok(filename) = CStr::from_ptr(filename).to_str() else {
+        return ptr::null_mut();
+    };
+    match Cty::new(filename) {
+        Ok(cty) => Box::into_raw(Box::new(CtyDatabase::new(cty))),
+        Err(_) => ptr::null_mut(),
+    }
+}
+
+/// Look up a callsign, filling `out` and returning true if it was found.
+///
+/// # Safety
+///
+/// `db` must come from `cty_load`, `callsign` must be a NUL terminated string
+/// and `out` must point to a writable `CtyEntity`.
+#[no_mangle]
+pub unsafe extern "C" fn cty_lookup(
+    db: *const CtyDatabase,
+    callsign: *const c_char,
+    out: *mut CtyEntity,
+) -> bool {
+    if db.is_null() || callsign.is_null() || out.is_null() {
+        return false;
+    }
+    let db = &*db;
+    let Ok(callsign) = CStr::from_ptr(callsign).to_str() else {
+        return false;
+    };
+    let Some(entity) = db.cty.lookup(callsign) else {
+        return false;
+    };
+    let Some(strings) = db.strings.get(&(entity as *const Entity)) else {
+        return false;
+    };
+    *out = CtyEntity {
+        name: strings.name.as_ptr(),
+        cq: entity.cq.into(),
+        itu: entity.itu.into(),
+        continent: strings.continent.as_ptr(),
+        lat: entity.lat,
+        lon: entity.lon,
+        utc_offset: entity.utc_offset(),
+        prefix: strings.prefix.as_ptr(),
+        waedc: entity.waedc,
+        dxcc: entity.dxcc,
+    };
+    true
+}
+
+/// Free a database, invalidating the strings of entities looked up in it.
+///
+/// # Safety
+///
+/// `db` must come from `cty_load` and not be used afterwards. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn cty_free(db: *mut CtyDatabase) {
+    if !db.is_null() {
+        drop(Box::from_raw(db));
+    }
+}
@@ -0,0 +1,105 @@
+// Differences between two versions of a country file
+use crate::{Cty, Entity, Prefix};
+use alloc::vec::Vec;
+use core::hash::Hash;
+use hashbrown::HashMap;
+
+// Entities are matched by primary prefix and prefixes by pattern,
+// all lists are sorted by those keys
+#[derive(Debug, Default)]
+pub struct Diff<'a> {
+    pub added_entities: Vec<&'a Entity>,
+    pub removed_entities: Vec<&'a Entity>,
+    // Old and new version
+    pub changed_entities: Vec<(&'a Entity, &'a Entity)>,
+    pub added_prefixes: Vec<&'a Prefix>,
+    pub removed_prefixes: Vec<&'a Prefix>,
+    // Old and new version
+    pub changed_prefixes: Vec<(&'a Prefix, &'a Prefix)>,
+}
+
+impl Diff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.added_entities.is_empty()
+            && self.removed_entities.is_empty()
+            && self.changed_entities.is_empty()
+            && self.added_prefixes.is_empty()
+            && self.removed_prefixes.is_empty()
+            && self.changed_prefixes.is_empty()
+    }
+}
+
+// Prefixes moved to another entity or with other overrides, changes of the
+// entity itself are only reported as a changed entity
+fn prefix_changed(old: &Prefix, new: &Prefix) -> bool {
+    old.is_exact != new.is_exact
+        || old.overrides != new.overrides
+        || old.entity.prefix != new.entity.prefix
+}
+
+// Any field differs, entities only compare equal by id
+fn entity_changed(old: &Entity, new: &Entity) -> bool {
+    old.id != new.id
+        || old.name != new.name
+        || old.cq != new.cq
+        || old.itu != new.itu
+        || old.continent != new.continent
+        || old.lat != new.lat
+        || old.lon != new.lon
+        || old.timezone != new.timezone
+        || old.waedc != new.waedc
+        || old.dxcc != new.dxcc
+        || old.deleted != new.deleted
+        || old.most_wanted != new.most_wanted
+        || old.localized_names != new.localized_names
+}
+
+fn compare<'a, T, K: Ord + Hash>(
+    old: impl Iterator<Item = &'a T>,
+    new: impl Iterator<Item = &'a T>,
+    key: impl Fn(&T) -> K,
+    changed: impl Fn(&T, &T) -> bool,
+) -> (Vec<&'a T>, Vec<&'a T>, Vec<(&'a T, &'a T)>) {
+    let new = new.map(|t| (key(t), t)).collect::<HashMap<_, _>>();
+    let mut old = old.map(|t| (key(t), t)).collect::<HashMap<_, _>>();
+    let mut added = Vec::new();
+    let mut changes = Vec::new();
+    for (k, n) in &new {
+        match old.remove(k) {
+            Some(o) if changed(o, n) => changes.push((o, *n)),
+            Some(_) => {}
+            None => added.push(*n),
+        }
+    }
+    let mut removed = old.into_values().collect::<Vec<_>>();
+    added.sort_by_key(|t| key(t));
+    removed.sort_by_key(|t| key(t));
+    changes.sort_by_key(|(t, _)| key(t));
+    (added, removed, changes)
+}
+
+impl Cty {
+    // What changed going from this database to `other`
+    pub fn diff<'a>(&'a self, other: &'a Cty) -> Diff<'a> {
+        let (added_entities, removed_entities, changed_entities) = compare(
+            self.entities(),
+            other.entities(),
+            |e| e.prefix.clone(),
+            entity_changed,
+        );
+        let (added_prefixes, removed_prefixes, changed_prefixes) = compare(
+            self.prefixes(),
+            other.prefixes(),
+            |p| p.pattern.clone(),
+            prefix_changed,
+        );
+        Diff {
+            added_entities,
+            removed_entities,
+            changed_entities,
+            added_prefixes,
+            removed_prefixes,
+            changed_prefixes,
+        }
+    }
+}
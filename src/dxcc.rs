@@ -0,0 +1,844 @@
+use core::fmt;
+
+// ARRL/ADIF DXCC entity codes keyed by cty.dat primary prefix.
+// WAE-only entities (marked with '*' in cty.dat) carry the code of their parent DXCC entity.
+// Sorted by prefix so it can be binary searched.
+const DXCC_CODES: &[(&str, u32)] = &[
+    ("1A", 246),
+    ("1S", 247),
+    ("3A", 260),
+    ("3B6", 4),
+    ("3B8", 165),
+    ("3B9", 207),
+    ("3C", 49),
+    ("3C0", 195),
+    ("3D2", 176),
+    ("3D2/c", 489),
+    ("3D2/r", 460),
+    ("3DA", 468),
+    ("3V", 474),
+    ("3W", 293),
+    ("3X", 107),
+    ("3Y/b", 24),
+    ("3Y/p", 199),
+    ("4J", 18),
+    ("4L", 75),
+    ("4O", 514),
+    ("4S", 315),
+    ("4U1I", 117),
+    ("4U1U", 289),
+    ("4U1V", 206),
+    ("4W", 511),
+    ("4X", 336),
+    ("5A", 436),
+    ("5B", 215),
+    ("5H", 470),
+    ("5N", 450),
+    ("5R", 438),
+    ("5T", 444),
+    ("5U", 187),
+    ("5V", 483),
+    ("5W", 190),
+    ("5X", 286),
+    ("5Z", 430),
+    ("6W", 456),
+    ("6Y", 82),
+    ("7O", 492),
+    ("7P", 432),
+    ("7Q", 440),
+    ("7X", 400),
+    ("8P", 62),
+    ("8Q", 159),
+    ("8R", 129),
+    ("9A", 497),
+    ("9G", 424),
+    ("9H", 257),
+    ("9J", 482),
+    ("9K", 348),
+    ("9L", 458),
+    ("9M2", 299),
+    ("9M6", 46),
+    ("9N", 369),
+    ("9Q", 414),
+    ("9U", 404),
+    ("9V", 381),
+    ("9X", 454),
+    ("9Y", 90),
+    ("A2", 402),
+    ("A3", 160),
+    ("A4", 370),
+    ("A5", 306),
+    ("A6", 391),
+    ("A7", 376),
+    ("A9", 304),
+    ("AP", 372),
+    ("BS7", 506),
+    ("BV", 386),
+    ("BV9P", 505),
+    ("BY", 318),
+    ("C2", 157),
+    ("C3", 203),
+    ("C5", 422),
+    ("C6", 60),
+    ("C9", 181),
+    ("CE", 112),
+    ("CE0X", 217),
+    ("CE0Y", 47),
+    ("CE0Z", 125),
+    ("CE9", 13),
+    ("CM", 70),
+    ("CN", 446),
+    ("CP", 104),
+    ("CT", 272),
+    ("CT3", 256),
+    ("CU", 149),
+    ("CX", 144),
+    ("CY0", 211),
+    ("CY9", 252),
+    ("D2", 401),
+    ("D4", 409),
+    ("D6", 411),
+    ("DL", 230),
+    ("DU", 375),
+    ("E3", 51),
+    ("E4", 510),
+    ("E5/n", 191),
+    ("E5/s", 234),
+    ("E6", 188),
+    ("E7", 501),
+    ("EA", 281),
+    ("EA6", 21),
+    ("EA8", 29),
+    ("EA9", 32),
+    ("EI", 245),
+    ("EK", 14),
+    ("EL", 434),
+    ("EP", 330),
+    ("ER", 179),
+    ("ES", 52),
+    ("ET", 53),
+    ("EU", 27),
+    ("EX", 135),
+    ("EY", 262),
+    ("EZ", 280),
+    ("F", 227),
+    ("FG", 79),
+    ("FH", 169),
+    ("FJ", 516),
+    ("FK", 162),
+    ("FK/c", 512),
+    ("FM", 84),
+    ("FO", 175),
+    ("FO/a", 508),
+    ("FO/c", 36),
+    ("FO/m", 509),
+    ("FP", 277),
+    ("FR", 453),
+    ("FS", 213),
+    ("FT/g", 99),
+    ("FT/j", 124),
+    ("FT/t", 276),
+    ("FT/w", 41),
+    ("FT/x", 131),
+    ("FT/z", 10),
+    ("FW", 298),
+    ("FY", 63),
+    ("G", 223),
+    ("GD", 114),
+    ("GI", 265),
+    ("GJ", 122),
+    ("GM", 279),
+    ("GM/s", 279),
+    ("GU", 106),
+    ("GW", 294),
+    ("H4", 185),
+    ("H40", 507),
+    ("HA", 239),
+    ("HB", 287),
+    ("HB0", 251),
+    ("HC", 120),
+    ("HC8", 71),
+    ("HH", 78),
+    ("HI", 72),
+    ("HK", 116),
+    ("HK0/a", 216),
+    ("HK0/m", 161),
+    ("HL", 137),
+    ("HP", 88),
+    ("HR", 80),
+    ("HS", 387),
+    ("HV", 295),
+    ("HZ", 378),
+    ("I", 248),
+    ("IG9", 248),
+    ("IS", 225),
+    ("IT9", 248),
+    ("J2", 382),
+    ("J3", 77),
+    ("J5", 109),
+    ("J6", 97),
+    ("J7", 95),
+    ("J8", 98),
+    ("JA", 339),
+    ("JD/m", 177),
+    ("JD/o", 192),
+    ("JT", 363),
+    ("JW", 259),
+    ("JW/b", 259),
+    ("JX", 118),
+    ("JY", 342),
+    ("K", 291),
+    ("KG4", 105),
+    ("KH0", 166),
+    ("KH1", 20),
+    ("KH2", 103),
+    ("KH3", 123),
+    ("KH4", 174),
+    ("KH5", 197),
+    ("KH6", 110),
+    ("KH7K", 138),
+    ("KH8", 9),
+    ("KH8/s", 515),
+    ("KH9", 297),
+    ("KL", 6),
+    ("KP1", 182),
+    ("KP2", 285),
+    ("KP4", 202),
+    ("KP5", 43),
+    ("LA", 266),
+    ("LU", 100),
+    ("LX", 254),
+    ("LY", 146),
+    ("LZ", 212),
+    ("OA", 136),
+    ("OD", 354),
+    ("OE", 206),
+    ("OH", 224),
+    ("OH0", 5),
+    ("OJ0", 167),
+    ("OK", 503),
+    ("OM", 504),
+    ("ON", 209),
+    ("OX", 237),
+    ("OY", 222),
+    ("OZ", 221),
+    ("P2", 163),
+    ("P4", 91),
+    ("P5", 344),
+    ("PA", 263),
+    ("PJ2", 517),
+    ("PJ4", 520),
+    ("PJ5", 519),
+    ("PJ7", 518),
+    ("PY", 108),
+    ("PY0F", 56),
+    ("PY0S", 253),
+    ("PY0T", 273),
+    ("PZ", 140),
+    ("R1FJ", 61),
+    ("S0", 302),
+    ("S2", 305),
+    ("S5", 499),
+    ("S7", 379),
+    ("S9", 219),
+    ("SM", 284),
+    ("SP", 269),
+    ("ST", 466),
+    ("SU", 478),
+    ("SV", 236),
+    ("SV/a", 180),
+    ("SV5", 45),
+    ("SV9", 40),
+    ("T2", 282),
+    ("T30", 301),
+    ("T31", 31),
+    ("T32", 48),
+    ("T33", 490),
+    ("T5", 232),
+    ("T7", 278),
+    ("T8", 22),
+    ("TA", 390),
+    ("TA1", 390),
+    ("TF", 242),
+    ("TG", 76),
+    ("TI", 308),
+    ("TI9", 37),
+    ("TJ", 406),
+    ("TK", 214),
+    ("TL", 408),
+    ("TN", 412),
+    ("TR", 420),
+    ("TT", 410),
+    ("TU", 428),
+    ("TY", 416),
+    ("TZ", 442),
+    ("UA", 54),
+    ("UA2", 126),
+    ("UA9", 15),
+    ("UK", 292),
+    ("UN", 130),
+    ("UR", 288),
+    ("V2", 94),
+    ("V3", 66),
+    ("V4", 249),
+    ("V5", 464),
+    ("V6", 173),
+    ("V7", 168),
+    ("V8", 345),
+    ("VE", 1),
+    ("VK", 150),
+    ("VK0H", 111),
+    ("VK0M", 153),
+    ("VK9C", 38),
+    ("VK9L", 147),
+    ("VK9M", 171),
+    ("VK9N", 189),
+    ("VK9W", 303),
+    ("VK9X", 35),
+    ("VP2E", 12),
+    ("VP2M", 96),
+    ("VP2V", 65),
+    ("VP5", 89),
+    ("VP6", 172),
+    ("VP6/d", 513),
+    ("VP8", 141),
+    ("VP8/g", 235),
+    ("VP8/h", 241),
+    ("VP8/o", 238),
+    ("VP8/s", 240),
+    ("VP9", 64),
+    ("VQ9", 33),
+    ("VR", 321),
+    ("VU", 324),
+    ("VU4", 11),
+    ("VU7", 142),
+    ("XE", 50),
+    ("XF4", 204),
+    ("XT", 480),
+    ("XU", 312),
+    ("XW", 143),
+    ("XX9", 152),
+    ("XZ", 309),
+    ("YA", 3),
+    ("YB", 327),
+    ("YI", 333),
+    ("YJ", 158),
+    ("YK", 384),
+    ("YL", 145),
+    ("YN", 86),
+    ("YO", 275),
+    ("YS", 74),
+    ("YU", 296),
+    ("YV", 148),
+    ("YV0", 17),
+    ("Z2", 452),
+    ("Z3", 502),
+    ("Z6", 522),
+    ("Z8", 521),
+    ("ZA", 7),
+    ("ZB", 233),
+    ("ZC4", 283),
+    ("ZD7", 250),
+    ("ZD8", 205),
+    ("ZD9", 274),
+    ("ZF", 69),
+    ("ZK3", 270),
+    ("ZL", 170),
+    ("ZL7", 34),
+    ("ZL8", 133),
+    ("ZL9", 16),
+    ("ZP", 132),
+    ("ZS", 462),
+    ("ZS8", 201),
+];
+
+pub(crate) fn dxcc_code(prefix: &str) -> u32 {
+    DXCC_CODES
+        .binary_search_by_key(&prefix, |&(p, _)| p)
+        .map(|i| DXCC_CODES[i].1)
+        .unwrap_or(0)
+}
+
+// Identifiers of the WAE-only entities, which share the DXCC code of their
+// parent. New ones get the next free number.
+const WAE_IDS: &[(&str, u16)] = &[
+    ("4U1V", 1001),
+    ("GM/s", 1002),
+    ("IG9", 1003),
+    ("IT9", 1004),
+    ("JW/b", 1005),
+    ("TA1", 1006),
+];
+
+// Identifies an entity independently of its name and its record in the file,
+// two bytes to store in log records instead of the country name. DXCC
+// entities use their code and WAE-only entities a number above 1000, so both
+// stay the same across releases of cty.dat. Entities without either are
+// numbered by their position from 0x8000 up, which is only stable for
+// reloads of the same files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct EntityId(pub u16);
+
+impl EntityId {
+    // Whether the id is positional rather than a DXCC or WAE number
+    pub fn is_positional(&self) -> bool {
+        self.0 & 0x8000 != 0
+    }
+}
+
+impl From<u16> for EntityId {
+    fn from(id: u16) -> EntityId {
+        EntityId(id)
+    }
+}
+
+impl From<EntityId> for u16 {
+    fn from(id: EntityId) -> u16 {
+        id.0
+    }
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+pub(crate) fn entity_id(prefix: &str, waedc: bool, dxcc: u32, index: usize) -> EntityId {
+    let wae = WAE_IDS.iter().find(|&&(p, _)| waedc && p == prefix);
+    match (wae, dxcc) {
+        (Some(&(_, id)), _) => EntityId(id),
+        (None, 1..) => EntityId(dxcc as u16),
+        (None, 0) => EntityId(0x8000 | index as u16),
+    }
+}
+
+// Deleted DXCC entities by ADIF code, sorted by code. cty.dat only lists
+// current entities, logs and other sources can still refer to these.
+const DELETED: &[(u32, &str)] = &[
+    (2, "Abu Ail Is."),
+    (8, "Aldabra"),
+    (19, "Bajo Nuevo"),
+    (23, "Blenheim Reef"),
+    (25, "British North Borneo"),
+    (26, "British Somaliland"),
+    (28, "Canal Zone"),
+    (30, "Celebe & Molucca Is."),
+    (39, "Comoros"),
+    (42, "Damao, Diu"),
+    (44, "Desroches"),
+    (55, "Farquhar"),
+    (57, "French Equatorial Africa"),
+    (58, "French Indo-China"),
+    (59, "French West Africa"),
+    (67, "French India"),
+    (68, "Kuwait/Saudi Arabia Neutral Zone"),
+    (81, "Germany"),
+    (85, "Bonaire, Curacao"),
+    (93, "Geyser Reef"),
+    (101, "Goa"),
+    (102, "Gold Coast, Togoland"),
+    (113, "Ifni"),
+    (115, "Italian Somaliland"),
+    (119, "Java"),
+    (127, "Kamaran Is."),
+    (128, "Karelo-Finnish Republic"),
+    (134, "Kingman Reef"),
+    (139, "Kuria Muria I."),
+    (151, "Malyj Vysotskij I."),
+    (154, "Yemen Arab Republic"),
+    (155, "Malaya"),
+    (164, "Manchuria"),
+    (178, "Minerva Reef"),
+    (183, "Netherlands Borneo"),
+    (184, "Netherlands New Guinea"),
+    (186, "Newfoundland, Labrador"),
+    (193, "Okinawa (Ryukyu Is.)"),
+    (194, "Okino Tori-shima"),
+    (196, "Palestine"),
+    (198, "Papua Territory"),
+    (200, "Portuguese Timor"),
+    (208, "Ruanda-Urundi"),
+    (210, "Saar"),
+    (218, "Czechoslovakia"),
+    (220, "Sarawak"),
+    (226, "Saudi Arabia/Iraq Neutral Zone"),
+    (228, "Serrana Bank & Roncador Cay"),
+    (229, "German Democratic Republic"),
+    (231, "Sikkim"),
+    (243, "People's Dem. Rep. of Yemen"),
+    (244, "Southern Sudan"),
+    (255, "St. Maarten, Saba, St. Eustatius"),
+    (258, "Sumatra"),
+    (261, "Swan Is."),
+    (264, "Tangier"),
+    (267, "Territory of New Guinea"),
+    (268, "Tibet"),
+    (271, "Trieste"),
+    (307, "Zanzibar"),
+    (488, "Walvis Bay"),
+    (493, "Penguin Is."),
+];
+
+// Name of a deleted DXCC entity, None for current and unknown codes
+pub fn deleted_dxcc_name(code: u32) -> Option<&'static str> {
+    DELETED
+        .binary_search_by_key(&code, |&(c, _)| c)
+        .ok()
+        .map(|i| DELETED[i].1)
+}
+
+// Country names of the current DXCC entities as the ADIF DXCC_Entity_Code
+// enumeration spells them, which LoTW and eQSL expect in the COUNTRY field.
+// Sorted by code.
+const ADIF_NAMES: &[(u32, &str)] = &[
+    (1, "CANADA"),
+    (3, "AFGHANISTAN"),
+    (4, "AGALEGA & ST. BRANDON IS."),
+    (5, "ALAND IS."),
+    (6, "ALASKA"),
+    (7, "ALBANIA"),
+    (9, "AMERICAN SAMOA"),
+    (10, "AMSTERDAM & ST. PAUL IS."),
+    (11, "ANDAMAN & NICOBAR IS."),
+    (12, "ANGUILLA"),
+    (13, "ANTARCTICA"),
+    (14, "ARMENIA"),
+    (15, "ASIATIC RUSSIA"),
+    (16, "NEW ZEALAND SUBANTARCTIC ISLANDS"),
+    (17, "AVES I."),
+    (18, "AZERBAIJAN"),
+    (20, "BAKER & HOWLAND IS."),
+    (21, "BALEARIC IS."),
+    (22, "PALAU"),
+    (24, "BOUVET"),
+    (27, "BELARUS"),
+    (29, "CANARY IS."),
+    (31, "C. KIRIBATI (BRITISH PHOENIX IS.)"),
+    (32, "CEUTA & MELILLA"),
+    (33, "CHAGOS IS."),
+    (34, "CHATHAM IS."),
+    (35, "CHRISTMAS I."),
+    (36, "CLIPPERTON I."),
+    (37, "COCOS I."),
+    (38, "COCOS (KEELING) IS."),
+    (40, "CRETE"),
+    (41, "CROZET I."),
+    (43, "DESECHEO I."),
+    (45, "DODECANESE"),
+    (46, "EAST MALAYSIA"),
+    (47, "EASTER I."),
+    (48, "E. KIRIBATI (LINE IS.)"),
+    (49, "EQUATORIAL GUINEA"),
+    (50, "MEXICO"),
+    (51, "ERITREA"),
+    (52, "ESTONIA"),
+    (53, "ETHIOPIA"),
+    (54, "EUROPEAN RUSSIA"),
+    (56, "FERNANDO DE NORONHA"),
+    (60, "BAHAMAS"),
+    (61, "FRANZ JOSEF LAND"),
+    (62, "BARBADOS"),
+    (63, "FRENCH GUIANA"),
+    (64, "BERMUDA"),
+    (65, "BRITISH VIRGIN IS."),
+    (66, "BELIZE"),
+    (69, "CAYMAN IS."),
+    (70, "CUBA"),
+    (71, "GALAPAGOS IS."),
+    (72, "DOMINICAN REPUBLIC"),
+    (74, "EL SALVADOR"),
+    (75, "GEORGIA"),
+    (76, "GUATEMALA"),
+    (77, "GRENADA"),
+    (78, "HAITI"),
+    (79, "GUADELOUPE"),
+    (80, "HONDURAS"),
+    (82, "JAMAICA"),
+    (84, "MARTINIQUE"),
+    (86, "NICARAGUA"),
+    (88, "PANAMA"),
+    (89, "TURKS & CAICOS IS."),
+    (90, "TRINIDAD & TOBAGO"),
+    (91, "ARUBA"),
+    (94, "ANTIGUA & BARBUDA"),
+    (95, "DOMINICA"),
+    (96, "MONTSERRAT"),
+    (97, "ST. LUCIA"),
+    (98, "ST. VINCENT"),
+    (99, "GLORIOSO IS."),
+    (100, "ARGENTINA"),
+    (103, "GUAM"),
+    (104, "BOLIVIA"),
+    (105, "GUANTANAMO BAY"),
+    (106, "GUERNSEY"),
+    (107, "GUINEA"),
+    (108, "BRAZIL"),
+    (109, "GUINEA-BISSAU"),
+    (110, "HAWAII"),
+    (111, "HEARD I."),
+    (112, "CHILE"),
+    (114, "ISLE OF MAN"),
+    (116, "COLOMBIA"),
+    (117, "ITU HQ"),
+    (118, "JAN MAYEN"),
+    (120, "ECUADOR"),
+    (122, "JERSEY"),
+    (123, "JOHNSTON I."),
+    (124, "JUAN DE NOVA, EUROPA"),
+    (125, "JUAN FERNANDEZ IS."),
+    (126, "KALININGRAD"),
+    (129, "GUYANA"),
+    (130, "KAZAKHSTAN"),
+    (131, "KERGUELEN IS."),
+    (132, "PARAGUAY"),
+    (133, "KERMADEC IS."),
+    (135, "KYRGYZSTAN"),
+    (136, "PERU"),
+    (137, "REPUBLIC OF KOREA"),
+    (138, "KURE I."),
+    (140, "SURINAME"),
+    (141, "FALKLAND IS."),
+    (142, "LAKSHADWEEP IS."),
+    (143, "LAOS"),
+    (144, "URUGUAY"),
+    (145, "LATVIA"),
+    (146, "LITHUANIA"),
+    (147, "LORD HOWE I."),
+    (148, "VENEZUELA"),
+    (149, "AZORES"),
+    (150, "AUSTRALIA"),
+    (152, "MACAO"),
+    (153, "MACQUARIE I."),
+    (157, "NAURU"),
+    (158, "VANUATU"),
+    (159, "MALDIVES"),
+    (160, "TONGA"),
+    (161, "MALPELO I."),
+    (162, "NEW CALEDONIA"),
+    (163, "PAPUA NEW GUINEA"),
+    (165, "MAURITIUS"),
+    (166, "MARIANA IS."),
+    (167, "MARKET REEF"),
+    (168, "MARSHALL IS."),
+    (169, "MAYOTTE"),
+    (170, "NEW ZEALAND"),
+    (171, "MELLISH REEF"),
+    (172, "PITCAIRN I."),
+    (173, "MICRONESIA"),
+    (174, "MIDWAY I."),
+    (175, "FRENCH POLYNESIA"),
+    (176, "FIJI"),
+    (177, "MINAMI TORISHIMA"),
+    (179, "MOLDOVA"),
+    (180, "MOUNT ATHOS"),
+    (181, "MOZAMBIQUE"),
+    (182, "NAVASSA I."),
+    (185, "SOLOMON IS."),
+    (187, "NIGER"),
+    (188, "NIUE"),
+    (189, "NORFOLK I."),
+    (190, "SAMOA"),
+    (191, "NORTH COOK IS."),
+    (192, "OGASAWARA"),
+    (195, "ANNOBON I."),
+    (197, "PALMYRA & JARVIS IS."),
+    (199, "PETER 1 I."),
+    (201, "PRINCE EDWARD & MARION IS."),
+    (202, "PUERTO RICO"),
+    (203, "ANDORRA"),
+    (204, "REVILLAGIGEDO"),
+    (205, "ASCENSION I."),
+    (206, "AUSTRIA"),
+    (207, "RODRIGUES I."),
+    (209, "BELGIUM"),
+    (211, "SABLE I."),
+    (212, "BULGARIA"),
+    (213, "SAINT MARTIN"),
+    (214, "CORSICA"),
+    (215, "CYPRUS"),
+    (216, "SAN ANDRES & PROVIDENCIA"),
+    (217, "SAN FELIX & SAN AMBROSIO"),
+    (219, "SAO TOME & PRINCIPE"),
+    (221, "DENMARK"),
+    (222, "FAROE IS."),
+    (223, "ENGLAND"),
+    (224, "FINLAND"),
+    (225, "SARDINIA"),
+    (227, "FRANCE"),
+    (230, "FEDERAL REPUBLIC OF GERMANY"),
+    (232, "SOMALIA"),
+    (233, "GIBRALTAR"),
+    (234, "SOUTH COOK IS."),
+    (235, "SOUTH GEORGIA I."),
+    (236, "GREECE"),
+    (237, "GREENLAND"),
+    (238, "SOUTH ORKNEY IS."),
+    (239, "HUNGARY"),
+    (240, "SOUTH SANDWICH IS."),
+    (241, "SOUTH SHETLAND IS."),
+    (242, "ICELAND"),
+    (245, "IRELAND"),
+    (246, "SOVEREIGN MILITARY ORDER OF MALTA"),
+    (247, "SPRATLY IS."),
+    (248, "ITALY"),
+    (249, "ST. KITTS & NEVIS"),
+    (250, "ST. HELENA"),
+    (251, "LIECHTENSTEIN"),
+    (252, "ST. PAUL I."),
+    (253, "ST. PETER & ST. PAUL ROCKS"),
+    (254, "LUXEMBOURG"),
+    (256, "MADEIRA IS."),
+    (257, "MALTA"),
+    (259, "SVALBARD"),
+    (260, "MONACO"),
+    (262, "TAJIKISTAN"),
+    (263, "NETHERLANDS"),
+    (265, "NORTHERN IRELAND"),
+    (266, "NORWAY"),
+    (269, "POLAND"),
+    (270, "TOKELAU IS."),
+    (272, "PORTUGAL"),
+    (273, "TRINDADE & MARTIM VAZ IS."),
+    (274, "TRISTAN DA CUNHA & GOUGH I."),
+    (275, "ROMANIA"),
+    (276, "TROMELIN I."),
+    (277, "ST. PIERRE & MIQUELON"),
+    (278, "SAN MARINO"),
+    (279, "SCOTLAND"),
+    (280, "TURKMENISTAN"),
+    (281, "SPAIN"),
+    (282, "TUVALU"),
+    (283, "UK SOVEREIGN BASE AREAS ON CYPRUS"),
+    (284, "SWEDEN"),
+    (285, "VIRGIN IS."),
+    (286, "UGANDA"),
+    (287, "SWITZERLAND"),
+    (288, "UKRAINE"),
+    (289, "UNITED NATIONS HQ"),
+    (291, "UNITED STATES OF AMERICA"),
+    (292, "UZBEKISTAN"),
+    (293, "VIET NAM"),
+    (294, "WALES"),
+    (295, "VATICAN"),
+    (296, "SERBIA"),
+    (297, "WAKE I."),
+    (298, "WALLIS & FUTUNA IS."),
+    (299, "WEST MALAYSIA"),
+    (301, "W. KIRIBATI (GILBERT IS. )"),
+    (302, "WESTERN SAHARA"),
+    (303, "WILLIS I."),
+    (304, "BAHRAIN"),
+    (305, "BANGLADESH"),
+    (306, "BHUTAN"),
+    (308, "COSTA RICA"),
+    (309, "MYANMAR"),
+    (312, "CAMBODIA"),
+    (315, "SRI LANKA"),
+    (318, "CHINA"),
+    (321, "HONG KONG"),
+    (324, "INDIA"),
+    (327, "INDONESIA"),
+    (330, "IRAN"),
+    (333, "IRAQ"),
+    (336, "ISRAEL"),
+    (339, "JAPAN"),
+    (342, "JORDAN"),
+    (344, "DEMOCRATIC PEOPLE'S REP. OF KOREA"),
+    (345, "BRUNEI DARUSSALAM"),
+    (348, "KUWAIT"),
+    (354, "LEBANON"),
+    (363, "MONGOLIA"),
+    (369, "NEPAL"),
+    (370, "OMAN"),
+    (372, "PAKISTAN"),
+    (375, "PHILIPPINES"),
+    (376, "QATAR"),
+    (378, "SAUDI ARABIA"),
+    (379, "SEYCHELLES"),
+    (381, "SINGAPORE"),
+    (382, "DJIBOUTI"),
+    (384, "SYRIA"),
+    (386, "TAIWAN"),
+    (387, "THAILAND"),
+    (390, "TURKEY"),
+    (391, "UNITED ARAB EMIRATES"),
+    (400, "ALGERIA"),
+    (401, "ANGOLA"),
+    (402, "BOTSWANA"),
+    (404, "BURUNDI"),
+    (406, "CAMEROON"),
+    (408, "CENTRAL AFRICA"),
+    (409, "CAPE VERDE"),
+    (410, "CHAD"),
+    (411, "COMOROS"),
+    (412, "REPUBLIC OF THE CONGO"),
+    (414, "DEMOCRATIC REPUBLIC OF THE CONGO"),
+    (416, "BENIN"),
+    (420, "GABON"),
+    (422, "THE GAMBIA"),
+    (424, "GHANA"),
+    (428, "COTE D'IVOIRE"),
+    (430, "KENYA"),
+    (432, "LESOTHO"),
+    (434, "LIBERIA"),
+    (436, "LIBYA"),
+    (438, "MADAGASCAR"),
+    (440, "MALAWI"),
+    (442, "MALI"),
+    (444, "MAURITANIA"),
+    (446, "MOROCCO"),
+    (450, "NIGERIA"),
+    (452, "ZIMBABWE"),
+    (453, "REUNION I."),
+    (454, "RWANDA"),
+    (456, "SENEGAL"),
+    (458, "SIERRA LEONE"),
+    (460, "ROTUMA I."),
+    (462, "SOUTH AFRICA"),
+    (464, "NAMIBIA"),
+    (466, "SUDAN"),
+    (468, "KINGDOM OF ESWATINI"),
+    (470, "TANZANIA"),
+    (474, "TUNISIA"),
+    (478, "EGYPT"),
+    (480, "BURKINA FASO"),
+    (482, "ZAMBIA"),
+    (483, "TOGO"),
+    (489, "CONWAY REEF"),
+    (490, "BANABA I. (OCEAN I.)"),
+    (492, "YEMEN"),
+    (497, "CROATIA"),
+    (499, "SLOVENIA"),
+    (501, "BOSNIA-HERZEGOVINA"),
+    (502, "NORTH MACEDONIA (REPUBLIC OF)"),
+    (503, "CZECH REPUBLIC"),
+    (504, "SLOVAK REPUBLIC"),
+    (505, "PRATAS I."),
+    (506, "SCARBOROUGH REEF"),
+    (507, "TEMOTU PROVINCE"),
+    (508, "AUSTRAL I."),
+    (509, "MARQUESAS IS."),
+    (510, "PALESTINE"),
+    (511, "TIMOR-LESTE"),
+    (512, "CHESTERFIELD IS."),
+    (513, "DUCIE I."),
+    (514, "MONTENEGRO"),
+    (515, "SWAINS I."),
+    (516, "SAINT BARTHELEMY"),
+    (517, "CURACAO"),
+    (518, "SINT MAARTEN"),
+    (519, "SABA & ST. EUSTATIUS"),
+    (520, "BONAIRE"),
+    (521, "SOUTH SUDAN (REPUBLIC OF)"),
+    (522, "REPUBLIC OF KOSOVO"),
+];
+
+// ADIF country name of a DXCC code, e.g. "FEDERAL REPUBLIC OF GERMANY" for 230
+pub fn adif_country_name(code: u32) -> Option<&'static str> {
+    ADIF_NAMES
+        .binary_search_by_key(&code, |&(c, _)| c)
+        .ok()
+        .map(|i| ADIF_NAMES[i].1)
+}
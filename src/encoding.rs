@@ -0,0 +1,32 @@
+// Text of country files that may not be plain UTF-8
+#[cfg(not(feature = "encoding"))]
+use alloc::format;
+use alloc::{borrow::Cow, boxed::Box};
+use core::error::Error;
+
+// UTF-8, with or without a byte order mark
+#[cfg(not(feature = "encoding"))]
+pub(crate) fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, Box<dyn Error>> {
+    let bytes = bytes.strip_prefix(b"\xef\xbb\xbf").unwrap_or(bytes);
+    match core::str::from_utf8(bytes) {
+        Ok(text) => Ok(Cow::Borrowed(text)),
+        Err(err) => {
+            Err(format!("Not UTF-8 ({err}), the encoding feature reads other encodings").into())
+        }
+    }
+}
+
+// UTF-8 or UTF-16 with a byte order mark, UTF-8 without one if it is valid,
+// otherwise Windows-1252, the superset of Latin-1 older country files use
+#[cfg(feature = "encoding")]
+pub(crate) fn decode(bytes: &[u8]) -> Result<Cow<'_, str>, Box<dyn Error>> {
+    use encoding_rs::{Encoding, WINDOWS_1252};
+
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return Ok(encoding.decode_with_bom_removal(bytes).0);
+    }
+    Ok(match core::str::from_utf8(bytes) {
+        Ok(text) => Cow::Borrowed(text),
+        Err(_) => WINDOWS_1252.decode_without_bom_handling(bytes).0,
+    })
+}
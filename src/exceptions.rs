@@ -0,0 +1,132 @@
+// Local exception lists kept in TOML or JSON instead of cty.dat syntax, e.g.
+//
+//     [[exception]]
+//     call = "K1ABC/KH6"
+//     entity = "KH6"
+//
+//     [[exception]]
+//     prefix = "VE2X"
+//     cq = 2
+//
+// A call pins the exact callsign, a prefix the calls starting with it. Without
+// an entity the call or prefix keeps the one it resolves to and only gets the
+// zone and coordinate corrections. The JSON form is {"exception": [...]}.
+use crate::{CqZone, Cty, ItuZone, Longitude, Overrides, Prefix};
+use serde::Deserialize;
+use std::{error::Error, path::Path};
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Exception {
+    pub call: Option<String>,
+    pub prefix: Option<String>,
+    // Primary prefix of the entity, e.g. "KH6"
+    pub entity: Option<String>,
+    pub cq: Option<u32>,
+    pub itu: Option<u32>,
+    pub continent: Option<String>,
+    pub lat: Option<f32>,
+    // Positive west like cty.dat, unless the builder says otherwise
+    pub lon: Option<f32>,
+    // Hours east of UTC, e.g. -10 for Hawaii
+    pub utc_offset: Option<f32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Exceptions {
+    #[serde(default, rename = "exception")]
+    pub exceptions: Vec<Exception>,
+}
+
+impl Exceptions {
+    pub fn from_toml(data: &str) -> Result<Exceptions, Box<dyn Error>> {
+        Ok(toml::from_str(data)?)
+    }
+    pub fn from_json(data: &str) -> Result<Exceptions, Box<dyn Error>> {
+        Ok(serde_json::from_str(data)?)
+    }
+    // The format is picked by the file extension, .json or else TOML
+    pub fn load<P: AsRef<Path>>(filename: P) -> Result<Exceptions, Box<dyn Error>> {
+        let path = filename.as_ref();
+        let data = std::fs::read_to_string(path)?;
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => Exceptions::from_json(&data),
+            _ => Exceptions::from_toml(&data),
+        }
+    }
+}
+
+impl Exception {
+    fn pattern(&self) -> Result<(String, bool), Box<dyn Error>> {
+        match (&self.call, &self.prefix) {
+            (Some(call), None) => Ok((call.trim().to_ascii_uppercase(), true)),
+            (None, Some(prefix)) => Ok((prefix.trim().to_ascii_uppercase(), false)),
+            _ => Err("Exception needs either a call or a prefix".into()),
+        }
+    }
+    fn overrides(&self, longitude: Longitude) -> Result<Overrides, Box<dyn Error>> {
+        let timezone = match self.utc_offset {
+            Some(hours) => {
+                Some(crate::offset_east((hours * 3600f32) as i32).ok_or("Invalid UTC offset")?)
+            }
+            None => None,
+        };
+        Ok(Overrides {
+            cq: self.cq.map(CqZone::try_from).transpose()?,
+            itu: self.itu.map(ItuZone::try_from).transpose()?,
+            lat: self.lat,
+            lon: match longitude {
+                Longitude::West => self.lon,
+                Longitude::East => self.lon.map(|lon| -lon),
+            },
+            continent: self.continent.as_deref().map(str::parse).transpose()?,
+            timezone,
+        })
+    }
+}
+
+impl Cty {
+    // Add the exceptions on top of the database, replacing prefixes or exact
+    // calls with the same pattern
+    pub fn apply_exceptions(&mut self, exceptions: &Exceptions) -> Result<(), Box<dyn Error>> {
+        self.apply_exceptions_with(exceptions, Longitude::West)
+    }
+    pub(crate) fn apply_exceptions_with(
+        &mut self,
+        exceptions: &Exceptions,
+        longitude: Longitude,
+    ) -> Result<(), Box<dyn Error>> {
+        for exception in &exceptions.exceptions {
+            let (pattern, is_exact) = exception.pattern()?;
+            let index = match &exception.entity {
+                Some(prefix) => self
+                    .entities
+                    .iter()
+                    .position(|e| e.prefix.eq_ignore_ascii_case(prefix))
+                    .ok_or_else(|| format!("Unknown entity {prefix} for {pattern}"))?,
+                // Calls keep the entity a lookup gives them, portable calls
+                // and rules included
+                None if is_exact => {
+                    let entity = self
+                        .resolve(&pattern, &mut None)
+                        .ok_or_else(|| format!("No entity for {pattern}"))?;
+                    self.entities
+                        .iter()
+                        .position(|e| e.prefix == entity.prefix)
+                        .ok_or_else(|| format!("No entity for {pattern}"))?
+                }
+                None => {
+                    self.find_prefix(&pattern, &mut None)
+                        .ok_or_else(|| format!("No entity for {pattern}"))?
+                        .entity_index
+                }
+            };
+            let overrides = exception.overrides(longitude)?;
+            let prefix = Prefix::new(&pattern, is_exact, overrides, &self.entities[index], index);
+            self.insert_prefix(prefix);
+        }
+        self.build_indexes();
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+// Step by step account of a lookup, for finding out why a call resolved
+// to an unexpected entity or zone
+use crate::{Cty, Entity, Prefix, Rule};
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+#[derive(Debug, Clone)]
+pub enum Step {
+    // The call was trimmed and uppercased
+    Normalized(String),
+    // The call was taken apart at its slashes
+    Split {
+        base: String,
+        location: Option<String>,
+        // Modifiers like P and MM, which don't change the location
+        dropped: Vec<String>,
+    },
+    // A call area number moved the home call, UA0XYZ for UA3XYZ/0
+    Relocated(String),
+    // The designator is where the station operates
+    Designator(String),
+    // The designator matched no prefix, so the home call is used
+    UnknownDesignator(String),
+    // A pattern without an entry, in the order they were tried
+    Tried(String),
+    // The entry the call matched
+    Matched(Prefix),
+    // A special-case rule sent the call to another entity
+    Rule {
+        rule: Rule,
+        call: String,
+        entity: Entity,
+    },
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Normalized(call) => write!(f, "normalized to {call}"),
+            Step::Split {
+                base,
+                location,
+                dropped,
+            } => {
+                write!(f, "home call {base}")?;
+                if let Some(location) = location {
+                    write!(f, ", designator {location}")?;
+                }
+                for modifier in dropped {
+                    write!(f, ", ignoring /{modifier}")?;
+                }
+                Ok(())
+            }
+            Step::Relocated(call) => write!(f, "call area moved, looking up {call}"),
+            Step::Designator(location) => write!(f, "looking up the designator {location}"),
+            Step::UnknownDesignator(location) => {
+                write!(f, "{location} is not a prefix, looking up the home call")
+            }
+            Step::Tried(pattern) => write!(f, "no entry for {pattern}"),
+            Step::Matched(prefix) => {
+                let kind = if prefix.is_exact {
+                    "exact call"
+                } else {
+                    "prefix"
+                };
+                write!(
+                    f,
+                    "matched {kind} {} of {}",
+                    prefix.pattern, prefix.entity.name
+                )?;
+                if !prefix.overrides.is_empty() {
+                    let entity = prefix.resolved();
+                    write!(f, ", overriding to CQ {} ITU {}", entity.cq, entity.itu)?;
+                }
+                Ok(())
+            }
+            Step::Rule { rule, call, entity } => {
+                write!(
+                    f,
+                    "{rule:?} moved {call} to {} ({})",
+                    entity.name, entity.prefix
+                )
+            }
+        }
+    }
+}
+
+// Result of `Cty::explain`
+#[derive(Debug, Clone)]
+pub struct Explanation<'a> {
+    pub callsign: String,
+    pub steps: Vec<Step>,
+    // What `lookup` returns for the call
+    pub entity: Option<&'a Entity>,
+}
+
+// One step per line, then the result
+impl fmt::Display for Explanation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}:", self.callsign)?;
+        for (i, step) in self.steps.iter().enumerate() {
+            writeln!(f, "  {}. {step}", i + 1)?;
+        }
+        match self.entity {
+            Some(entity) => write!(f, "  => {entity}"),
+            None => write!(f, "  => not found"),
+        }
+    }
+}
+
+// Record a step if a lookup is being traced
+pub(crate) fn note(trace: &mut Option<&mut Vec<Step>>, step: impl FnOnce() -> Step) {
+    if let Some(trace) = trace {
+        trace.push(step());
+    }
+}
+
+impl Cty {
+    // How `lookup` resolves a callsign, e.g. for EA8/DL1ABC/P: the /P is
+    // ignored, EA8 is taken as the location and matches Canary Islands
+    pub fn explain(&self, callsign: &str) -> Explanation<'_> {
+        let mut steps = Vec::new();
+        let entity = self
+            .resolve(callsign, &mut Some(&mut steps))
+            .map(|e| e.as_ref());
+        Explanation {
+            callsign: String::from(callsign),
+            steps,
+            entity,
+        }
+    }
+}
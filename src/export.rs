@@ -0,0 +1,110 @@
+// JSON export of the parsed database, for consumers that don't want to
+// parse cty.dat themselves
+use crate::{Continent, CqZone, Cty, Entity, ItuZone, Overrides};
+use serde::Serialize;
+
+#[derive(Serialize)]
+struct Export<'a> {
+    version: Option<&'a str>,
+    // In file order
+    entities: Vec<&'a Entity>,
+    // In pattern order
+    prefixes: Vec<ExportedPrefix<'a>>,
+}
+
+#[derive(Serialize)]
+struct ExportedPrefix<'a> {
+    pattern: &'a str,
+    exact: bool,
+    // Primary prefix of the entity
+    entity: &'a str,
+    #[serde(skip_serializing_if = "Overrides::is_empty")]
+    overrides: &'a Overrides,
+}
+
+#[derive(Serialize)]
+struct FeatureCollection<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    features: Vec<Feature<'a>>,
+}
+
+#[derive(Serialize)]
+struct Feature<'a> {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    geometry: Point,
+    properties: Properties<'a>,
+}
+
+#[derive(Serialize)]
+struct Point {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    // Longitude east and latitude, in that order, as the file gives them
+    coordinates: [f32; 2],
+}
+
+#[derive(Serialize)]
+struct Properties<'a> {
+    name: &'a str,
+    prefix: &'a str,
+    dxcc: u32,
+    cq: CqZone,
+    itu: ItuZone,
+    continent: &'a Continent,
+    waedc: bool,
+}
+
+impl Cty {
+    // The entities and prefixes as one JSON object, prefixes refer to their
+    // entity by primary prefix and only carry the overrides they set, e.g.
+    // {"version":"20231103","entities":[...],"prefixes":[{"pattern":"DA",
+    // "exact":false,"entity":"DL"},...]}
+    pub fn to_json(&self) -> String {
+        let export = Export {
+            version: self.version(),
+            entities: self.entities().collect(),
+            prefixes: self
+                .prefixes_sorted()
+                .map(|p| ExportedPrefix {
+                    pattern: &p.pattern,
+                    exact: p.is_exact,
+                    entity: &p.entity.prefix,
+                    overrides: &p.overrides,
+                })
+                .collect(),
+        };
+        // Serializing plain data to a string can't fail
+        serde_json::to_string(&export).unwrap_or_default()
+    }
+    // A GeoJSON FeatureCollection with a point at the coordinates of every
+    // entity, in file order, carrying its name, prefix, DXCC code, zones and
+    // continent as properties
+    pub fn to_geojson(&self) -> String {
+        let features = self
+            .entities()
+            .map(|entity| Feature {
+                kind: "Feature",
+                geometry: Point {
+                    kind: "Point",
+                    coordinates: [-entity.lon, entity.lat],
+                },
+                properties: Properties {
+                    name: &entity.name,
+                    prefix: &entity.prefix,
+                    dxcc: entity.dxcc,
+                    cq: entity.cq,
+                    itu: entity.itu,
+                    continent: &entity.continent,
+                    waedc: entity.waedc,
+                },
+            })
+            .collect();
+        let collection = FeatureCollection {
+            kind: "FeatureCollection",
+            features,
+        };
+        serde_json::to_string(&collection).unwrap_or_default()
+    }
+}
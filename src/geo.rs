@@ -0,0 +1,54 @@
+// Great-circle calculations on a spherical earth.
+// Coordinates are in degrees with north latitude and east longitude positive.
+
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Path {
+    // Short path distance in km
+    pub distance: f64,
+    // Short path beam heading in degrees from true north
+    pub bearing: f64,
+    // Long path distance in km, the rest of the great circle
+    pub long_distance: f64,
+    // Long path beam heading, opposite of the short path
+    pub long_bearing: f64,
+}
+
+pub fn distance(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (lon2 - lon1).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+pub fn bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let dlon = (lon2 - lon1).to_radians();
+    let y = dlon.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlon.cos();
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+pub fn path(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Path {
+    let distance = distance(lat1, lon1, lat2, lon2);
+    let bearing = bearing(lat1, lon1, lat2, lon2);
+    Path {
+        distance,
+        bearing,
+        long_distance: 2.0 * std::f64::consts::PI * EARTH_RADIUS_KM - distance,
+        long_bearing: (bearing + 180.0).rem_euclid(360.0),
+    }
+}
+
+// Point on the unit sphere, the dot product of two is the cosine of their angular distance
+pub(crate) fn unit_vector(lat: f64, lon: f64) -> [f64; 3] {
+    let (lat, lon) = (lat.to_radians(), lon.to_radians());
+    [lat.cos() * lon.cos(), lat.cos() * lon.sin(), lat.sin()]
+}
+
+pub(crate) fn dot(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
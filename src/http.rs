@@ -0,0 +1,111 @@
+// Loading country files from a web server, e.g. an internal mirror
+use crate::{Cty, CtyBuilder, Diff, ParseError, ParseErrors, ParseWarning, Parser};
+use std::{error::Error, io::Read, time::Duration};
+
+// Defaults for `CtyBuilder::load_url`, generous for files of a few hundred kB
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+pub const DEFAULT_MAX_SIZE: u64 = 16 * 1024 * 1024;
+
+// Download a file and parse it as it arrives, failing on HTTP errors, on
+// taking longer than `timeout` altogether and on bodies larger than
+// `max_size` bytes
+fn download(
+    url: &str,
+    mut parser: Parser,
+    timeout: Duration,
+    max_size: u64,
+) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+    let agent: ureq::Agent = ureq::Agent::config_builder()
+        .timeout_global(Some(timeout))
+        .build()
+        .into();
+    let mut response = agent.get(url).call()?;
+    let body = response.body_mut().with_config().limit(max_size).reader();
+    #[cfg(feature = "compressed")]
+    let body = crate::compressed::reader(body, max_size)?;
+    // The limit applies to the decompressed file as well
+    let mut body = body.take(max_size + 1);
+    let mut chunk = vec![0; 16 * 1024];
+    let mut size = 0;
+    loop {
+        match body.read(&mut chunk)? {
+            0 => return parser.finish(),
+            n => {
+                size += n as u64;
+                if size > max_size {
+                    return Err(format!("File larger than {max_size} bytes").into());
+                }
+                parser.feed(&chunk[..n])?
+            }
+        }
+    }
+}
+
+impl CtyBuilder {
+    // Time allowed for a download as a whole
+    pub fn http_timeout(mut self, timeout: Duration) -> CtyBuilder {
+        self.http_timeout = Some(timeout);
+        self
+    }
+
+    // Largest file `load_url` accepts, in bytes
+    pub fn max_download_size(mut self, bytes: u64) -> CtyBuilder {
+        self.max_download_size = Some(bytes);
+        self
+    }
+
+    // Like `load` for a file downloaded over HTTP(S), the added files are
+    // still read from disk
+    pub fn load_url(&self, url: &str) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        let parsed = download(
+            url,
+            self.parser(),
+            self.http_timeout.unwrap_or(DEFAULT_TIMEOUT),
+            self.max_download_size.unwrap_or(DEFAULT_MAX_SIZE),
+        )
+        .map_err(|err| -> Box<dyn Error> {
+            // Parse errors already say where they are
+            match err.is::<ParseError>() || err.is::<ParseErrors>() {
+                true => err,
+                false => format!("{url}: {err}").into(),
+            }
+        })?;
+        self.layer(parsed)
+    }
+}
+
+impl Cty {
+    pub fn load_url(url: &str) -> Result<Cty, Box<dyn Error>> {
+        CtyBuilder::new().load_url(url).map(|(cty, _)| cty)
+    }
+}
+
+// A newer release found by `Cty::check_for_update`
+#[derive(Debug)]
+pub struct Update {
+    pub version: Option<String>,
+    pub cty: Cty,
+}
+
+impl Update {
+    // What the release changes compared to the loaded database
+    pub fn changes<'a>(&'a self, current: &'a Cty) -> Diff<'a> {
+        current.diff(&self.cty)
+    }
+}
+
+impl Cty {
+    // Download the published file at `url` and return it if it is a newer
+    // release. Files without release markers count as newer when they differ.
+    pub fn check_for_update(&self, url: &str) -> Result<Option<Update>, Box<dyn Error>> {
+        let (latest, _) = CtyBuilder::new().load_url(url)?;
+        let newer = match (self.version(), latest.version()) {
+            (Some(current), Some(version)) => version > current,
+            _ => !self.diff(&latest).is_empty(),
+        };
+        Ok(newer.then(|| Update {
+            version: latest.version().map(str::to_string),
+            cty: latest,
+        }))
+    }
+}
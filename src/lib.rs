@@ -1,162 +1,956 @@
-use chrono::FixedOffset;
-use regex::Regex;
-use std::{
-    collections::HashMap,
-    error::Error,
-    fs::File,
-    io::{self, BufRead},
-    path::Path,
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "adif")]
+pub mod adif;
+mod builder;
+#[cfg(feature = "cabrillo")]
+pub mod cabrillo;
+mod callsign;
+#[cfg(feature = "clublog")]
+pub mod clublog;
+#[cfg(feature = "codegen")]
+pub mod codegen;
+#[cfg(feature = "arrow")]
+pub mod columnar;
+#[cfg(feature = "compressed")]
+mod compressed;
+mod continent;
+#[cfg(feature = "ffi")]
+pub mod cty_ffi;
+mod diff;
+mod dxcc;
+mod encoding;
+#[cfg(feature = "exceptions")]
+mod exceptions;
+mod explain;
+#[cfg(feature = "json")]
+mod export;
+#[cfg(feature = "std")]
+pub mod geo;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod lint;
+#[cfg(feature = "localization")]
+pub mod localization;
+#[cfg(feature = "std")]
+pub mod maidenhead;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod mutate;
+mod parser;
+#[cfg(feature = "python")]
+mod python;
+mod region;
+mod resolver;
+mod rules;
+#[cfg(feature = "service")]
+mod service;
+mod static_cty;
+mod stats;
+mod stream;
+mod suggest;
+#[cfg(all(feature = "std", feature = "timezone"))]
+pub mod sun;
+mod table;
+mod tracker;
+mod validate;
+mod version;
+#[cfg(feature = "watch")]
+mod watch;
+mod zone;
+
+pub use builder::{CtyBuilder, Longitude, PortableCalls};
+pub use callsign::{is_plausible_callsign, wpx_prefix, Callsign};
+pub use continent::Continent;
+pub use diff::Diff;
+pub use dxcc::{adif_country_name, deleted_dxcc_name, EntityId};
+#[cfg(feature = "exceptions")]
+pub use exceptions::{Exception, Exceptions};
+use explain::note;
+pub use explain::{Explanation, Step};
+#[cfg(feature = "mmap")]
+pub use mmap::MappedFile;
+use parser::Record;
+pub use parser::{ParseError, ParseErrors, ParseWarning};
+pub use region::Region;
+pub use resolver::{CallsignResolver, ChainedResolver};
+pub use rules::Rule;
+#[cfg(feature = "service")]
+pub use service::CtyService;
+pub use static_cty::{StaticCty, StaticEntity, StaticPrefix};
+pub use stats::Stats;
+use stream::Loader;
+pub use stream::Parser;
+use table::{PrefixTable, ResolvedCache};
+pub use tracker::{MultiplierTracker, Multipliers, NewMultipliers};
+pub use validate::Issue;
+#[cfg(feature = "watch")]
+pub use watch::WatchedCty;
+pub use zone::{CqZone, ItuZone};
+
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
 };
+#[cfg(feature = "timezone")]
+use chrono::{DateTime, FixedOffset, Utc};
+use core::{convert::Infallible, error::Error, fmt, hash::Hash, str::FromStr};
+use hashbrown::HashMap;
+#[cfg(feature = "fs")]
+use std::path::Path;
+
+// Offset of an entity's standard time from UTC
+#[cfg(feature = "timezone")]
+pub type UtcOffset = FixedOffset;
+// Offset of an entity's standard time from UTC, in seconds east
+#[cfg(not(feature = "timezone"))]
+pub type UtcOffset = i32;
+
+// Offset of the given seconds east of UTC, None if more than a day
+#[cfg(feature = "timezone")]
+fn offset_east(seconds: i32) -> Option<UtcOffset> {
+    FixedOffset::east_opt(seconds)
+}
+#[cfg(not(feature = "timezone"))]
+fn offset_east(seconds: i32) -> Option<UtcOffset> {
+    (seconds.abs() < 86_400).then_some(seconds)
+}
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Entity {
     pub name: String,
-    pub cq: u32,
-    pub itu: u32,
-    pub continent: String,
+    pub cq: CqZone,
+    pub itu: ItuZone,
+    pub continent: Continent,
     pub lat: f32,
     pub lon: f32,
-    pub timezone: FixedOffset,
+    #[cfg_attr(
+        all(feature = "serde", feature = "timezone"),
+        serde(serialize_with = "serialize_offset")
+    )]
+    pub timezone: UtcOffset,
     pub prefix: String,
     pub waedc: bool,
-    pub is_exact: bool,
+    pub dxcc: u32,
+    pub id: EntityId,
+    // The DXCC entity no longer counts for new contacts
+    pub deleted: bool,
+    // Clublog most-wanted rank, if a list was loaded
+    pub most_wanted: Option<u32>,
+    // Names by language tag, if translations were loaded
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "BTreeMap::is_empty"))]
+    pub localized_names: BTreeMap<String, String>,
+}
+impl Entity {
+    // Latitude and longitude with east longitude positive,
+    // cty.dat itself stores longitude positive west
+    pub fn coordinates(&self) -> (f64, f64) {
+        (self.lat as f64, -self.lon as f64)
+    }
+    // The 4 character grid square containing the entity's coordinates
+    #[cfg(feature = "std")]
+    pub fn grid(&self) -> String {
+        let (lat, lon) = self.coordinates();
+        maidenhead::to_locator(lat, lon, 4).unwrap_or_default()
+    }
+    pub fn id(&self) -> EntityId {
+        self.id
+    }
+    // The entity's DXCC country as ADIF spells it, e.g. "ITALY" for Italy and
+    // for WAE-only Sicily, None for entities without a DXCC code
+    pub fn adif_country_name(&self) -> Option<&'static str> {
+        dxcc::adif_country_name(self.dxcc)
+    }
+    // The prefix identifying the entity in the country file, e.g. I for Italy
+    pub fn primary_prefix(&self) -> &str {
+        &self.prefix
+    }
+    // IARU region (1, 2 or 3) whose band plan applies, None for an unknown
+    // continent or Antarctica, which all three share
+    pub fn iaru_region(&self) -> Option<u8> {
+        match self.continent {
+            Continent::EU | Continent::AF => Some(1),
+            Continent::NA | Continent::SA => Some(2),
+            // Mongolia is in region 3 although its ITU zones are below 40
+            Continent::AS if self.dxcc == 363 => Some(3),
+            // The Middle East, Caucasus, Central Asia and Asiatic Russia are
+            // in ITU zones 39 and below, south and east Asia above
+            Continent::AS if self.itu.get() <= 39 => Some(1),
+            Continent::AS => Some(3),
+            // Hawaii and the US islands around it, but not Eastern Kiribati
+            Continent::OC if self.itu == 61 && self.dxcc != 48 => Some(2),
+            Continent::OC => Some(3),
+            Continent::AN | Continent::Unknown(_) => None,
+        }
+    }
+    // The `Display` form, for listings and status lines
+    pub fn summary(&self) -> String {
+        self.to_string()
+    }
+    // Seconds east of UTC
+    #[cfg(feature = "timezone")]
+    pub fn utc_offset(&self) -> i32 {
+        self.timezone.local_minus_utc()
+    }
+    // Seconds east of UTC
+    #[cfg(not(feature = "timezone"))]
+    pub fn utc_offset(&self) -> i32 {
+        self.timezone
+    }
+    // The entity's local (standard) time at the given instant
+    #[cfg(feature = "timezone")]
+    pub fn local_time(&self, time: DateTime<Utc>) -> DateTime<FixedOffset> {
+        time.with_timezone(&self.timezone)
+    }
+    // Sunrise and sunset at the entity's coordinates on the given UTC date
+    #[cfg(all(feature = "std", feature = "timezone"))]
+    pub fn sun_times(&self, date: chrono::NaiveDate) -> sun::SunTimes {
+        let (lat, lon) = self.coordinates();
+        sun::sun_times(lat, lon, date, self.timezone)
+    }
+    // Azimuth and elevation of the sun at the entity's coordinates
+    #[cfg(all(feature = "std", feature = "timezone"))]
+    pub fn sun_position(&self, time: DateTime<Utc>) -> sun::SunPosition {
+        let (lat, lon) = self.coordinates();
+        sun::sun_position(lat, lon, time)
+    }
+}
+// One line summary, e.g. "Fed. Rep. of Germany (DL) — CQ 14, ITU 28, EU, 51.0N 10.0E, UTC+1"
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (lat, lon) = self.coordinates();
+        write!(
+            f,
+            "{} ({}) — CQ {}, ITU {}, {}, {:.1}{} {:.1}{}, UTC",
+            self.name,
+            self.prefix,
+            self.cq,
+            self.itu,
+            self.continent,
+            lat.abs(),
+            if lat < 0.0 { 'S' } else { 'N' },
+            lon.abs(),
+            if lon < 0.0 { 'W' } else { 'E' }
+        )?;
+        let offset = self.utc_offset();
+        let sign = if offset < 0 { '-' } else { '+' };
+        let (hours, minutes) = (offset.abs() / 3600, offset.abs() % 3600 / 60);
+        match minutes {
+            0 => write!(f, "{sign}{hours}"),
+            _ => write!(f, "{sign}{hours}:{minutes:02}"),
+        }
+    }
+}
+// Entities are equal when they have the same id, so the records of prefixes
+// with zone or coordinate overrides are the same entity as the one they override
+impl PartialEq for Entity {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+impl Eq for Entity {}
+impl Hash for Entity {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
 }
 impl Default for Entity {
     fn default() -> Self {
         Entity {
             name: String::new(),
-            cq: 0,
-            itu: 0,
-            continent: String::new(),
+            // Zone 1 stands in for records that weren't loaded from a file
+            cq: CqZone::new(1).unwrap(),
+            itu: ItuZone::new(1).unwrap(),
+            continent: Continent::Unknown(String::new()),
             lat: 0.0,
             lon: 0.0,
-            timezone: FixedOffset::east_opt(0).unwrap(),
+            timezone: offset_east(0).unwrap(),
             prefix: String::new(),
             waedc: false,
-            is_exact: false,
+            dxcc: 0,
+            id: EntityId::default(),
+            deleted: false,
+            most_wanted: None,
+            localized_names: BTreeMap::new(),
+        }
+    }
+}
+
+// Per-prefix values that replace the ones of the entity
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Overrides {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub cq: Option<CqZone>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub itu: Option<ItuZone>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub lat: Option<f32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub lon: Option<f32>,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub continent: Option<Continent>,
+    #[cfg_attr(
+        all(feature = "serde", feature = "timezone"),
+        serde(serialize_with = "serialize_optional_offset")
+    )]
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub timezone: Option<UtcOffset>,
+}
+impl Overrides {
+    pub fn is_empty(&self) -> bool {
+        *self == Overrides::default()
+    }
+    fn apply(&self, entity: &Entity) -> Entity {
+        let mut entity = entity.clone();
+        if let Some(cq) = self.cq {
+            entity.cq = cq;
+        }
+        if let Some(itu) = self.itu {
+            entity.itu = itu;
+        }
+        if let Some(lat) = self.lat {
+            entity.lat = lat;
+        }
+        if let Some(lon) = self.lon {
+            entity.lon = lon;
+        }
+        if let Some(continent) = &self.continent {
+            entity.continent = continent.clone();
+        }
+        if let Some(timezone) = self.timezone {
+            entity.timezone = timezone;
+        }
+        entity
+    }
+}
+
+// The record of a prefix with the overrides, shared with the entity without any
+fn resolve(overrides: &Overrides, entity: &Arc<Entity>) -> Arc<Entity> {
+    match overrides.is_empty() {
+        true => entity.clone(),
+        false => Arc::new(overrides.apply(entity)),
+    }
+}
+
+// A prefix or exact callsign pointing at its canonical entity.
+// Prefixes without overrides share the entity record, only prefixes that
+// override zones, coordinates etc. get their own resolved copy.
+#[derive(Debug, Clone)]
+pub struct Prefix {
+    pub pattern: String,
+    pub is_exact: bool,
+    pub overrides: Overrides,
+    pub entity: Arc<Entity>,
+    resolved: Arc<Entity>,
+    entity_index: usize,
+    tokens: String,
+}
+impl Prefix {
+    fn new(
+        pattern: &str,
+        is_exact: bool,
+        overrides: Overrides,
+        entity: &Arc<Entity>,
+        entity_index: usize,
+    ) -> Prefix {
+        let resolved = resolve(&overrides, entity);
+        Prefix::with_resolved(pattern, is_exact, overrides, entity, entity_index, resolved)
+    }
+    fn with_resolved(
+        pattern: &str,
+        is_exact: bool,
+        overrides: Overrides,
+        entity: &Arc<Entity>,
+        entity_index: usize,
+        resolved: Arc<Entity>,
+    ) -> Prefix {
+        Prefix {
+            pattern: pattern.to_string(),
+            is_exact,
+            overrides,
+            entity: entity.clone(),
+            resolved,
+            entity_index,
+            tokens: String::new(),
         }
     }
+    // The entity with this prefix's overrides applied
+    pub fn resolved(&self) -> &Entity {
+        &self.resolved
+    }
+    // The override tokens exactly as the country file gave them, e.g.
+    // "(15)[28]<41.0/-5.0>", empty when the prefix inherits everything or
+    // wasn't read from a file
+    pub fn override_tokens(&self) -> &str {
+        &self.tokens
+    }
+    // Point the prefix at another entity record, sharing the resolved record
+    // with earlier prefixes of the entity with the same overrides
+    fn relink(&mut self, entity: &Arc<Entity>, entity_index: usize, resolved: &mut ResolvedCache) {
+        self.resolved = resolved.get(entity, entity_index, &self.overrides);
+        self.entity = entity.clone();
+        self.entity_index = entity_index;
+    }
+}
+
+// Options for `Cty::entity_by_name_with`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NameMatch {
+    pub case_insensitive: bool,
+    pub partial: bool,
 }
 
 #[derive(Debug, Default)]
 pub struct Cty {
-    pub entities: HashMap<String, Entity>,
+    entities: Vec<Arc<Entity>>,
+    prefixes: PrefixTable,
+    cq_zones: HashMap<CqZone, GroupIndex>,
+    itu_zones: HashMap<ItuZone, GroupIndex>,
+    continents: HashMap<Continent, GroupIndex>,
+    // Earlier definitions of a pattern replaced by a merged one for another
+    // entity or exactness
+    shadowed: Vec<Prefix>,
+    version: Option<String>,
+    // Applied in order after prefix matching
+    rules: Vec<Rule>,
+    normalize_calls: bool,
+    portable_calls: PortableCalls,
+    // Primary prefixes of entities changed at runtime, for `export_overrides`
+    edited_entities: BTreeSet<String>,
+    // Patterns of aliases changed at runtime with the definition they had
+    // before the first change, for `remove_alias` and `export_overrides`
+    edited_aliases: BTreeMap<String, Option<Prefix>>,
+    // Unit vectors of the entity coordinates, by entity index
+    #[cfg(feature = "std")]
+    points: Vec<[f64; 3]>,
+}
+
+// Entities and prefixes (by index) that resolve to a zone or continent
+#[derive(Debug, Default)]
+struct GroupIndex {
+    entities: Vec<usize>,
+    prefixes: Vec<usize>,
 }
 
-// The output is wrapped in a Result to allow matching on errors
-// Returns an Iterator to the Reader of the lines of the file.
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+// Contents of a country file as text, see `encoding::decode`
+#[cfg(feature = "fs")]
+fn read_file<P>(filename: P) -> Result<String, Box<dyn Error>>
 where
     P: AsRef<Path>,
 {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    let bytes = std::fs::read(filename)?;
+    #[cfg(feature = "compressed")]
+    let bytes = compressed::decompress(bytes, compressed::MAX_SIZE)?;
+    Ok(encoding::decode(&bytes)?.into_owned())
+}
+
+// Serialized as e.g. "+01:00"
+#[cfg(all(feature = "serde", feature = "timezone"))]
+fn serialize_offset<S: serde::Serializer>(
+    offset: &FixedOffset,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(offset)
+}
+#[cfg(all(feature = "serde", feature = "timezone"))]
+fn serialize_optional_offset<S: serde::Serializer>(
+    offset: &Option<FixedOffset>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match offset {
+        Some(offset) => serializer.collect_str(offset),
+        None => serializer.serialize_none(),
+    }
+}
+
+// Lines of an in-memory country file, in the form `parser::parse_lines` reads
+fn str_lines(data: &str) -> impl Iterator<Item = Result<&str, Infallible>> {
+    data.lines().map(Ok)
 }
 
-fn get_timezone_offset(tz: &str) -> Result<FixedOffset, Box<dyn Error>> {
-    let tz = tz.parse::<f32>()?;
-    FixedOffset::west_opt((tz * 3600f32) as i32).ok_or("Invalid timezone".into())
+// Parse the contents of a country file, e.g. one fetched by a web page
+impl FromStr for Cty {
+    type Err = Box<dyn Error>;
+
+    fn from_str(data: &str) -> Result<Cty, Box<dyn Error>> {
+        CtyBuilder::new().parse(data).map(|(cty, _)| cty)
+    }
 }
 
 impl Cty {
+    #[cfg(feature = "fs")]
     pub fn new(filename: &str) -> Result<Cty, Box<dyn Error>> {
-        let mut cty = Cty::default();
-        let mut last_entity = Entity::default();
-        let lines = read_lines(filename)?;
-
-        let cq_regex = Regex::new("\\((\\d+)\\)")?;
-        let itu_regex = Regex::new("\\[(\\d+)\\]")?;
-        let latlon_regex = Regex::new("<(.*)/(.*)>")?;
-        let continent_regex = Regex::new("\\{(.*)\\}")?;
-        let timezone_regex = Regex::new("~(.*)~")?;
-
-        for line in lines {
-            let line = line?;
-            let parts = line.split(':').map(str::trim).collect::<Vec<&str>>();
-
-            if parts.len() > 2 {
-                last_entity = Entity {
-                    name: parts[0].to_string(),
-                    cq: parts[1].parse::<u32>()?,
-                    itu: parts[2].parse::<u32>()?,
-                    continent: parts[3].to_string(),
-                    lat: parts[4].parse::<f32>()?,
-                    lon: parts[5].parse::<f32>()?,
-                    timezone: get_timezone_offset(parts[6])?,
-                    prefix: parts[7].trim_start_matches("*").to_string(),
-                    waedc: parts[7].starts_with('*'),
-                    is_exact: false,
-                };
-                cty.entities
-                    .insert(last_entity.prefix.clone(), last_entity.clone());
-            } else {
-                let aliases = line
-                    .trim_end_matches(';')
-                    .split(',')
-                    .filter(|s| !s.is_empty())
-                    .map(str::trim)
-                    .collect::<Vec<&str>>();
-
-                for alias in aliases {
-                    let is_exact = alias.starts_with('=');
-                    let alias = alias.trim_start_matches('=');
-                    // Get the string until end of string of one of the following characters is found: ([#~
-                    let pos = alias
-                        .find(|c| c == '(' || c == '[' || c == '#' || c == '~')
-                        .unwrap_or(alias.len());
-                    let override_alias = &alias[..pos];
-                    let overrides = &alias[pos..];
-                    let mut entity = last_entity.clone();
-                    entity.is_exact = is_exact;
-                    // Match by (.*)
-                    let cq_override = cq_regex.captures(overrides);
-                    if cq_override.is_some() {
-                        entity.cq = cq_override.unwrap()[1].parse::<u32>()?;
-                    }
-                    // Match by [.*]
-                    let itu_override = itu_regex.captures(overrides);
-                    if itu_override.is_some() {
-                        entity.itu = itu_override.unwrap()[1].parse::<u32>()?;
-                    }
-                    // Match by <.*/.*>
-                    let latlon_override = latlon_regex.captures(overrides);
-                    if latlon_override.is_some() {
-                        let latlon = latlon_override.unwrap();
-                        entity.lat = latlon[1].parse::<f32>()?;
-                        entity.lon = latlon[2].parse::<f32>()?;
-                    }
-                    // Match by {.*}
-                    let continent_override = continent_regex.captures(overrides);
-                    if continent_override.is_some() {
-                        entity.continent = continent_override.unwrap()[1].to_string();
-                    }
-                    // Match by ~.*~
-                    let timezone_override = timezone_regex.captures(overrides);
-                    if timezone_override.is_some() {
-                        entity.timezone = get_timezone_offset(&timezone_override.unwrap()[1])?;
-                    }
-                    cty.entities.insert(override_alias.to_string(), entity);
+        CtyBuilder::new().load(filename).map(|(cty, _)| cty)
+    }
+    fn from_lines<L: AsRef<str>, E: Into<Box<dyn Error>>>(
+        lines: impl Iterator<Item = Result<L, E>>,
+        options: &CtyBuilder,
+    ) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        let mut loader = Loader::new(options);
+        let warnings = parser::parse_lines(lines, Loader::lenient(options), |_, record| {
+            loader.record(record);
+            Ok(())
+        })?;
+        loader.finish(warnings)
+    }
+    fn build_indexes(&mut self) {
+        let order = self.prefixes.sorted();
+        self.cq_zones = self.group_index(&order, |e| Some(e.cq));
+        self.itu_zones = self.group_index(&order, |e| Some(e.itu));
+        self.continents = self.group_index(&order, |e| Some(e.continent.clone()));
+        self.version = self.find_version();
+        #[cfg(feature = "std")]
+        {
+            self.points = self
+                .entities()
+                .map(|e| {
+                    let (lat, lon) = e.coordinates();
+                    geo::unit_vector(lat, lon)
+                })
+                .collect();
+        }
+    }
+    // Layer another database on top of this one. Entities with the same primary
+    // prefix are replaced by the overlay's record, and the overlay's prefixes
+    // replace the ones with the same pattern.
+    pub fn merge(&mut self, overlay: Cty) {
+        let indexes = overlay
+            .entities
+            .into_iter()
+            .map(|mut entity| {
+                let index = self.entities.iter().position(|e| e.prefix == entity.prefix);
+                let i = index.unwrap_or(self.entities.len());
+                // Positional ids follow the entity's place in this database
+                let id = dxcc::entity_id(&entity.prefix, entity.waedc, entity.dxcc, i);
+                if entity.id != id {
+                    Arc::make_mut(&mut entity).id = id;
+                }
+                match index {
+                    Some(i) => self.entities[i] = entity,
+                    None => self.entities.push(entity),
                 }
+                i
+            })
+            .collect::<Vec<_>>();
+        let mut resolved = ResolvedCache::default();
+        self.relink_prefixes(&mut resolved);
+        let mut relink = |mut prefix: Prefix, entities: &[Arc<Entity>]| {
+            let index = indexes[prefix.entity_index];
+            prefix.relink(&entities[index], index, &mut resolved);
+            prefix
+        };
+        for prefix in overlay.shadowed {
+            let prefix = relink(prefix, &self.entities);
+            self.shadowed.push(prefix);
+        }
+        let mut prefixes = overlay.prefixes.into_values().collect::<Vec<_>>();
+        prefixes.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+        for prefix in prefixes {
+            let prefix = relink(prefix, &self.entities);
+            self.insert_prefix(prefix);
+        }
+        self.build_indexes();
+    }
+    fn insert_prefix(&mut self, prefix: Prefix) {
+        let (entity_index, is_exact) = (prefix.entity_index, prefix.is_exact);
+        if let Some(old) = self.prefixes.insert(prefix) {
+            if old.entity_index != entity_index || old.is_exact != is_exact {
+                self.shadowed.push(old);
             }
         }
-        Ok(cty)
+    }
+    // Modify every entity, keeping prefixes and their overrides pointing at the new records
+    #[cfg(any(
+        all(test, feature = "fs"),
+        feature = "clublog",
+        feature = "localization"
+    ))]
+    fn update_entities(&mut self, f: impl Fn(&mut Entity)) {
+        for entity in &mut self.entities {
+            f(Arc::make_mut(entity));
+        }
+        self.relink_prefixes(&mut ResolvedCache::default());
+        self.build_indexes();
+    }
+    // Point prefixes at the current entity records after they were replaced.
+    // The cache must be new or only hold records of the current entities.
+    fn relink_prefixes(&mut self, resolved: &mut ResolvedCache) {
+        let edited = self.edited_aliases.values_mut().flatten();
+        for prefix in self
+            .prefixes
+            .values_mut()
+            .chain(&mut self.shadowed)
+            .chain(edited)
+        {
+            let index = prefix.entity_index;
+            prefix.relink(&self.entities[index], index, resolved);
+        }
+    }
+    // Group the prefixes, taken in the given order, by a key of their entity
+    fn group_index<K: Eq + Hash>(
+        &self,
+        order: &[usize],
+        key: impl Fn(&Entity) -> Option<K>,
+    ) -> HashMap<K, GroupIndex> {
+        let mut index: HashMap<K, GroupIndex> = HashMap::new();
+        for &i in order {
+            let prefix = &self.prefixes[i];
+            let Some(key) = key(&prefix.resolved) else {
+                continue;
+            };
+            let group = index.entry(key).or_default();
+            group.prefixes.push(i);
+            group.entities.push(prefix.entity_index);
+        }
+        for group in index.values_mut() {
+            group.entities.sort_unstable();
+            group.entities.dedup();
+        }
+        index
     }
     pub fn lookup(&self, callsign: &str) -> Option<&Entity> {
-        self.entities
-            .get(callsign)
-            .filter(|e| e.is_exact)
+        self.lookup_arc(callsign).map(Arc::as_ref)
+    }
+    // The shared entity record, which outlives the database and can be sent
+    // to other threads
+    pub fn lookup_owned(&self, callsign: &str) -> Option<Arc<Entity>> {
+        self.lookup_arc(callsign).cloned()
+    }
+    // Portable calls count for where they operate: W1ABC/KH6 is Hawaii and
+    // UA3XYZ/0 is looked up as UA0XYZ, with the zones of that prefix
+    pub(crate) fn lookup_arc(&self, callsign: &str) -> Option<&Arc<Entity>> {
+        self.resolve(callsign, &mut None)
+    }
+    // The lookup itself, recording its steps for `explain` if asked to
+    pub(crate) fn resolve(
+        &self,
+        callsign: &str,
+        trace: &mut Option<&mut Vec<Step>>,
+    ) -> Option<&Arc<Entity>> {
+        let normalized;
+        let callsign = match self.normalize_calls {
+            true => {
+                normalized = callsign.trim().to_ascii_uppercase();
+                note(trace, || Step::Normalized(normalized.clone()));
+                normalized.as_str()
+            }
+            false => callsign,
+        };
+        if let Some(prefix) = self.prefixes.get(callsign).filter(|p| p.is_exact) {
+            note(trace, || Step::Matched(prefix.clone()));
+            return Some(&prefix.resolved);
+        }
+        // Callsigns are ASCII, anything else is noise from a spot or log
+        if !callsign.is_ascii() {
+            return None;
+        }
+        let parts = callsign::split(callsign);
+        note(trace, || Step::Split {
+            base: parts.base.to_string(),
+            location: parts.location.map(str::to_string),
+            dropped: callsign::modifiers(callsign).map(str::to_string).collect(),
+        });
+        let relocated = parts
+            .call_area()
+            .filter(|_| self.portable_calls == PortableCalls::Location)
+            .map(|area| callsign::relocate(parts.base, area));
+        // The call to match the prefixes against and the one for the rules,
+        // None for designators
+        let (call, home) = match (self.portable_calls, &relocated, parts.location) {
+            (PortableCalls::Prefix, _, _) => (callsign, Some(parts.base)),
+            (PortableCalls::HomeCall, _, _) => (parts.base, Some(parts.base)),
+            (_, Some(relocated), _) => {
+                note(trace, || Step::Relocated(relocated.clone()));
+                (relocated.as_str(), Some(relocated.as_str()))
+            }
+            (_, None, Some(location)) => {
+                note(trace, || Step::Designator(location.to_string()));
+                (location, None)
+            }
+            (_, None, None) => (parts.base, Some(parts.base)),
+        };
+        let (home, prefix) = match self.find_prefix(call, trace) {
+            Some(prefix) => (home, prefix),
+            // Designators that aren't prefixes say nothing about the location
+            None if home.is_none() => {
+                note(trace, || Step::UnknownDesignator(call.to_string()));
+                (Some(parts.base), self.find_prefix(parts.base, trace)?)
+            }
+            None => return None,
+        };
+        note(trace, || Step::Matched(prefix.clone()));
+        // Exact calls are already special cases, rules are about home calls
+        if let Some(call) = home.filter(|_| !prefix.is_exact) {
+            let target = self.rules.iter().find_map(|rule| {
+                let target = rule.apply(call, &prefix.resolved)?;
+                Some((rule, target))
+            });
+            if let Some((rule, target)) = target.filter(|(_, t)| *t != prefix.entity.prefix) {
+                if let Some(entity) = self.entities.iter().find(|e| e.prefix == target) {
+                    note(trace, || Step::Rule {
+                        rule: rule.clone(),
+                        call: call.to_string(),
+                        entity: entity.as_ref().clone(),
+                    });
+                    return Some(entity);
+                }
+            }
+        }
+        Some(&prefix.resolved)
+    }
+    // Add a rule after the built-in ones, see `Rule`
+    pub fn add_rule(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+    // Remove all rules, including the built-in ones, leaving lookups to the prefixes
+    pub fn clear_rules(&mut self) {
+        self.rules.clear();
+    }
+    // The prefix entry a callsign resolves to, before rules are applied
+    pub fn lookup_prefix(&self, callsign: &str) -> Option<&Prefix> {
+        self.find_prefix(callsign, &mut None)
+    }
+    // Every entry matching the call as written, most specific first: exact
+    // calls, then prefixes from the longest down. Definitions replaced by a
+    // later one for another entity, e.g. by `add_alias` or a merged file,
+    // follow the entry that replaced them, newest first.
+    pub fn lookup_all(&self, callsign: &str) -> Vec<&Prefix> {
+        let mut matches = Vec::new();
+        let mut add = |pattern: &str, exact: bool| {
+            matches.extend(self.prefixes.get(pattern).filter(|p| p.is_exact == exact));
+            let edited = self.replaced_by_edit(pattern);
+            matches.extend(edited.filter(|p| p.is_exact == exact));
+            let replaced = self.shadowed.iter().rev();
+            matches.extend(replaced.filter(|p| p.is_exact == exact && p.pattern == pattern));
+        };
+        add(callsign, true);
+        for i in (1..=callsign.len()).rev() {
+            if callsign.is_char_boundary(i) {
+                add(&callsign[..i], false);
+            }
+        }
+        matches
+    }
+    // The exact call, or else the longest prefix of the call
+    fn find_prefix(&self, callsign: &str, trace: &mut Option<&mut Vec<Step>>) -> Option<&Prefix> {
+        if let Some(prefix) = self.prefixes.get(callsign).filter(|p| p.is_exact) {
+            return Some(prefix);
+        }
+        (1..=callsign.len())
+            .rev()
+            .filter(|&i| callsign.is_char_boundary(i))
+            .find_map(|i| {
+                let prefix = self.prefixes.get(&callsign[..i]);
+                if prefix.is_none() {
+                    note(trace, || Step::Tried(callsign[..i].to_string()));
+                }
+                prefix
+            })
+    }
+    pub fn entities(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.iter().map(Arc::as_ref)
+    }
+    pub fn prefixes(&self) -> impl Iterator<Item = &Prefix> {
+        self.prefixes.values()
+    }
+    // Every prefix and exact call of an entity, sorted by pattern. Resolved
+    // entities, e.g. from `lookup`, count for the entity they belong to.
+    pub fn aliases_of<'a>(&'a self, entity: &'a Entity) -> impl Iterator<Item = &'a Prefix> {
+        self.prefixes_sorted()
+            .filter(move |p| p.entity.prefix == entity.prefix)
+    }
+    // Entities whose DXCC entity was deleted, none for cty.dat itself
+    pub fn deleted_entities(&self) -> impl Iterator<Item = &Entity> {
+        self.entities().filter(|e| e.deleted)
+    }
+    // Entities in name order, for reproducible listings
+    pub fn entities_sorted_by_name(&self) -> impl Iterator<Item = &Entity> {
+        let mut entities = self.entities().collect::<Vec<_>>();
+        entities.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.prefix.cmp(&b.prefix)));
+        entities.into_iter()
+    }
+    // Prefixes in pattern order, `prefixes` has no defined order
+    pub fn prefixes_sorted(&self) -> impl Iterator<Item = &Prefix> {
+        let mut prefixes = self.prefixes().collect::<Vec<_>>();
+        prefixes.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+        prefixes.into_iter()
+    }
+    // Entities with at least one prefix in the CQ zone, in file order
+    pub fn entities_in_cq_zone(&self, zone: CqZone) -> impl Iterator<Item = &Entity> {
+        self.cq_zones
+            .get(&zone)
+            .into_iter()
+            .flat_map(|z| z.entities.iter().map(|&i| self.entities[i].as_ref()))
+    }
+    // Prefixes resolving to the CQ zone, sorted by pattern
+    pub fn prefixes_in_cq_zone(&self, zone: CqZone) -> impl Iterator<Item = &Prefix> {
+        self.cq_zones
+            .get(&zone)
+            .into_iter()
+            .flat_map(|z| z.prefixes.iter().map(|&i| &self.prefixes[i]))
+    }
+    // Entities with at least one prefix in the ITU zone, in file order
+    pub fn entities_in_itu_zone(&self, zone: ItuZone) -> impl Iterator<Item = &Entity> {
+        self.itu_zones
+            .get(&zone)
+            .into_iter()
+            .flat_map(|z| z.entities.iter().map(|&i| self.entities[i].as_ref()))
+    }
+    // Prefixes resolving to the ITU zone, sorted by pattern
+    pub fn prefixes_in_itu_zone(&self, zone: ItuZone) -> impl Iterator<Item = &Prefix> {
+        self.itu_zones
+            .get(&zone)
+            .into_iter()
+            .flat_map(|z| z.prefixes.iter().map(|&i| &self.prefixes[i]))
+    }
+    // Entities with at least one prefix on the continent, in file order
+    pub fn entities_in_continent(&self, continent: Continent) -> impl Iterator<Item = &Entity> {
+        self.continents
+            .get(&continent)
+            .into_iter()
+            .flat_map(|c| c.entities.iter().map(|&i| self.entities[i].as_ref()))
+    }
+    // Prefixes resolving to the continent, sorted by pattern
+    pub fn prefixes_in_continent(&self, continent: Continent) -> impl Iterator<Item = &Prefix> {
+        self.continents
+            .get(&continent)
+            .into_iter()
+            .flat_map(|c| c.prefixes.iter().map(|&i| &self.prefixes[i]))
+    }
+    // Great-circle path between the entities of two callsigns
+    #[cfg(feature = "std")]
+    pub fn path(&self, from: &str, to: &str) -> Option<geo::Path> {
+        let (lat1, lon1) = self.lookup(from)?.coordinates();
+        let (lat2, lon2) = self.lookup(to)?.coordinates();
+        Some(geo::path(lat1, lon1, lat2, lon2))
+    }
+    // Short and long path headings and distances from a home position (east
+    // longitude positive) to every entity, in file order, for rotator presets
+    // and printed heading charts
+    #[cfg(feature = "std")]
+    pub fn heading_table(&self, home_lat: f64, home_lon: f64) -> Vec<(&Entity, geo::Path)> {
+        self.entities()
+            .map(|entity| {
+                let (lat, lon) = entity.coordinates();
+                (entity, geo::path(home_lat, home_lon, lat, lon))
+            })
+            .collect()
+    }
+    // The entity whose coordinates are closest to a position (east longitude positive)
+    #[cfg(feature = "std")]
+    pub fn nearest_entity(&self, lat: f64, lon: f64) -> Option<&Entity> {
+        let point = geo::unit_vector(lat, lon);
+        self.points
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| geo::dot(a, &point).total_cmp(&geo::dot(b, &point)))
+            .map(|(i, _)| self.entities[i].as_ref())
+    }
+    // The k entities closest to a position with their distance in km,
+    // nearest first, e.g. to list the ones within 500 km of a locator
+    #[cfg(feature = "std")]
+    pub fn nearest_entities(&self, lat: f64, lon: f64, k: usize) -> Vec<(&Entity, f64)> {
+        let point = geo::unit_vector(lat, lon);
+        let mut closeness = self
+            .points
+            .iter()
+            .map(|p| geo::dot(p, &point))
+            .enumerate()
+            .collect::<Vec<_>>();
+        let by_closeness = |a: &(usize, f64), b: &(usize, f64)| b.1.total_cmp(&a.1);
+        if k < closeness.len() {
+            closeness.select_nth_unstable_by(k, by_closeness);
+            closeness.truncate(k);
+        }
+        closeness.sort_by(by_closeness);
+        closeness
+            .into_iter()
+            .map(|(i, _)| {
+                let entity = self.entities[i].as_ref();
+                let (to_lat, to_lon) = entity.coordinates();
+                (entity, geo::distance(lat, lon, to_lat, to_lon))
+            })
+            .collect()
+    }
+    // Local time at the entity of a callsign
+    #[cfg(feature = "timezone")]
+    pub fn local_time_for(
+        &self,
+        callsign: &str,
+        time: DateTime<Utc>,
+    ) -> Option<DateTime<FixedOffset>> {
+        self.lookup(callsign).map(|e| e.local_time(time))
+    }
+    // When both entities of two callsigns are within `margin` of sunrise or
+    // sunset on a UTC date, for low-band skeds. None if a call doesn't resolve.
+    #[cfg(all(feature = "std", feature = "timezone"))]
+    pub fn gray_line(
+        &self,
+        from: &str,
+        to: &str,
+        date: chrono::NaiveDate,
+        margin: chrono::Duration,
+    ) -> Option<Vec<sun::GrayLine>> {
+        let from = self.lookup(from)?.coordinates();
+        let to = self.lookup(to)?.coordinates();
+        Some(sun::gray_line(from, to, date, margin))
+    }
+    // Find an entity by its exact name, e.g. "Fed. Rep. of Germany".
+    // The primary prefix of the returned entity is `Entity::prefix`.
+    pub fn entity_by_name(&self, name: &str) -> Option<&Entity> {
+        self.entity_by_name_with(name, NameMatch::default())
+    }
+    // Find an entity by name, an exact match is preferred over a partial one
+    pub fn entity_by_name_with(&self, name: &str, options: NameMatch) -> Option<&Entity> {
+        let normalize = |s: &str| {
+            if options.case_insensitive {
+                s.to_lowercase()
+            } else {
+                s.to_string()
+            }
+        };
+        let name = normalize(name);
+        self.entities()
+            .find(|e| normalize(&e.name) == name)
             .or_else(|| {
-                (1..=callsign.len())
-                    .rev()
-                    .find_map(|i| self.entities.get(&callsign[..i]))
+                options
+                    .partial
+                    .then(|| self.entities().find(|e| normalize(&e.name).contains(&name)))
+                    .flatten()
             })
     }
+    // Find an entity by the id stored for it earlier, see `EntityId` for how
+    // long ids stay valid
+    pub fn entity_by_id(&self, id: EntityId) -> Option<&Entity> {
+        self.entities().find(|e| e.id == id)
+    }
+    // The entity with an ADIF DXCC code, e.g. 230 for Germany. WAE-only
+    // entities share the code of their DXCC entity and aren't returned.
+    pub fn entity_by_dxcc(&self, code: u32) -> Option<&Entity> {
+        self.entities()
+            .find(|e| code != 0 && !e.waedc && e.dxcc == code)
+    }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "fs"))]
 mod tests {
     use super::*;
 
+    fn cq(zone: u32) -> CqZone {
+        CqZone::new(zone).unwrap()
+    }
+
+    fn itu(zone: u32) -> ItuZone {
+        ItuZone::new(zone).unwrap()
+    }
+
     #[test]
     fn it_works() {
         let cty = Cty::new("cty.dat");
@@ -190,4 +984,1978 @@ mod tests {
         let entity = cty.lookup("BS7H").unwrap();
         assert_eq!(entity.name, "Scarborough Reef");
     }
+
+    #[test]
+    fn dxcc_lookup() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.lookup("JA1XYZ").unwrap().dxcc, 339);
+        assert_eq!(cty.lookup("DL1ABC").unwrap().dxcc, 230);
+        assert_eq!(cty.lookup("IT9ABC").unwrap().dxcc, 248);
+    }
+
+    #[test]
+    fn shared_entity() {
+        let cty = Cty::new("cty.dat").unwrap();
+        // S6 is a plain alias of 9V and shares the entity record
+        assert!(Arc::ptr_eq(
+            &cty.prefixes["9V"].resolved,
+            &cty.prefixes["S6"].resolved
+        ));
+    }
+
+    #[test]
+    fn shared_overrides() {
+        use std::collections::HashSet;
+
+        let records = |cty: &Cty| {
+            let records = cty.prefixes().map(|p| Arc::as_ptr(&p.resolved));
+            records.collect::<HashSet<_>>().len()
+        };
+        let mut cty = Cty::new("cty.dat").unwrap();
+        let loaded = records(&cty);
+        assert!(loaded < cty.prefixes().count() / 2);
+        // Edits after loading keep the sharing
+        cty.update_entities(|e| e.deleted = false);
+        assert_eq!(records(&cty), loaded);
+        let overlay = "Test Island:  40:  90:  OC:  1.0:  1.0:  0.0:  Q1:\n    Q1,Q2(1),Q3(1);\n";
+        cty.merge(overlay.parse().unwrap());
+        assert_eq!(records(&cty), loaded + 2);
+        assert!(Arc::ptr_eq(
+            &cty.prefixes["Q2"].resolved,
+            &cty.prefixes["Q3"].resolved
+        ));
+    }
+
+    #[test]
+    fn entity_iteration() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.entities().count(), 346);
+        assert!(cty.prefixes().count() > cty.entities().count());
+    }
+
+    #[test]
+    fn prefix_overrides() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let prefix = cty.lookup_prefix("B9U").unwrap();
+        assert_eq!(prefix.overrides.cq, Some(cq(23)));
+        assert_eq!(prefix.overrides.itu, Some(itu(42)));
+        assert_eq!(prefix.entity.name, "China");
+        assert_eq!(prefix.resolved().cq, 23);
+    }
+
+    #[test]
+    fn name_lookup() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let entity = cty.entity_by_name("Fed. Rep. of Germany").unwrap();
+        assert_eq!(entity.prefix, "DL");
+        assert!(cty.entity_by_name("fed. rep. of germany").is_none());
+
+        let options = NameMatch {
+            case_insensitive: true,
+            partial: true,
+        };
+        let entity = cty.entity_by_name_with("germany", options).unwrap();
+        assert_eq!(entity.prefix, "DL");
+        // The exact match wins over "African Italy"
+        let entity = cty.entity_by_name_with("italy", options).unwrap();
+        assert_eq!(entity.prefix, "I");
+    }
+
+    #[test]
+    fn cq_zone_index() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let names = cty
+            .entities_in_cq_zone(cq(14))
+            .map(|e| e.name.as_str())
+            .collect::<Vec<_>>();
+        assert!(names.contains(&"Fed. Rep. of Germany"));
+        assert!(!names.contains(&"Japan"));
+        // United States spans CQ zones 3, 4 and 5 via prefix overrides
+        assert!(cty.entities_in_cq_zone(cq(3)).any(|e| e.prefix == "K"));
+        assert!(cty
+            .prefixes_in_cq_zone(cq(14))
+            .all(|p| p.resolved().cq == 14));
+        assert_eq!(CqZone::new(41), None);
+    }
+
+    #[test]
+    fn itu_zone_index() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert!(cty.entities_in_itu_zone(itu(28)).any(|e| e.prefix == "DL"));
+        assert!(cty.entities_in_itu_zone(itu(45)).any(|e| e.prefix == "JA"));
+        assert!(cty
+            .prefixes_in_itu_zone(itu(28))
+            .all(|p| p.resolved().itu == 28));
+        assert_eq!(ItuZone::new(0), None);
+    }
+
+    #[test]
+    fn continent_index() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert!(cty
+            .entities_in_continent(Continent::EU)
+            .any(|e| e.prefix == "DL"));
+        assert!(cty
+            .entities_in_continent(Continent::EU)
+            .all(|e| e.prefix != "JA"));
+        // Asiatic Turkey is in AS, European Turkey in EU
+        assert!(cty
+            .entities_in_continent(Continent::AS)
+            .any(|e| e.prefix == "TA"));
+        assert!(cty
+            .entities_in_continent(Continent::EU)
+            .any(|e| e.prefix == "TA1"));
+        assert!(cty
+            .prefixes_in_continent(Continent::OC)
+            .all(|p| p.resolved().continent == Continent::OC));
+        assert_eq!("eu".parse::<Continent>().unwrap(), Continent::EU);
+        assert!("XX".parse::<Continent>().is_err());
+    }
+
+    #[test]
+    fn great_circle_path() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let path = cty.path("DL1ABC", "VK3XYZ").unwrap();
+        assert!((path.distance - 14_000.0).abs() < 500.0);
+        assert!(path.bearing > 60.0 && path.bearing < 90.0);
+        let back = cty.path("VK3XYZ", "DL1ABC").unwrap();
+        assert!((back.distance - path.distance).abs() < 1e-6);
+        assert!(cty.path("DL1ABC", "012").is_none());
+        // One degree of longitude on the equator
+        assert!((geo::distance(0.0, 0.0, 0.0, 1.0) - 111.19).abs() < 0.01);
+        assert_eq!(geo::bearing(0.0, 0.0, 10.0, 0.0), 0.0);
+        assert_eq!(geo::bearing(0.0, 0.0, 0.0, -10.0), 270.0);
+    }
+
+    #[test]
+    fn grid_locator() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.lookup("DL1ABC").unwrap().grid(), "JO51");
+        assert_eq!(cty.lookup("JA1XYZ").unwrap().grid(), "PM96");
+
+        assert_eq!(maidenhead::to_locator(51.0, 10.0, 6).unwrap(), "JO51aa");
+        assert_eq!(maidenhead::to_locator(-33.9, 18.4, 8).unwrap(), "JF96ec84");
+        assert_eq!(maidenhead::to_locator(90.0, 180.0, 2).unwrap(), "RR");
+        assert!(maidenhead::to_locator(0.0, 0.0, 5).is_err());
+
+        let (lat, lon) = maidenhead::from_locator("JO51").unwrap();
+        assert_eq!((lat, lon), (51.5, 11.0));
+        let (lat, lon) = maidenhead::from_locator("jf96ec84").unwrap();
+        assert!((lat + 33.9).abs() < 0.01 && (lon - 18.4).abs() < 0.01);
+        assert!(maidenhead::from_locator("ZZ").is_err());
+        assert!(maidenhead::from_locator("JO5").is_err());
+        for locator in [
+            "1O51", "JO51!!", "J@", "JOA1", "JO5Z", "JO51a5", "JO51aa9z", "ÄÖ",
+        ] {
+            assert!(maidenhead::from_locator(locator).is_err(), "{locator}");
+        }
+    }
+
+    #[cfg(feature = "timezone")]
+    #[test]
+    fn sunrise_sunset() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let date = chrono::NaiveDate::from_ymd_opt(2023, 6, 21).unwrap();
+        let times = cty.lookup("DL1ABC").unwrap().sun_times(date);
+        let sunrise = times.sunrise.unwrap();
+        let sunset = times.sunset.unwrap();
+        assert_eq!(sunrise.date_naive(), date);
+        assert_eq!(sunrise.format("%H").to_string(), "03");
+        assert_eq!(sunset.format("%H").to_string(), "19");
+        assert_eq!(
+            times.sunrise_local().unwrap().format("%H").to_string(),
+            "04"
+        );
+
+        // Midnight sun on Svalbard
+        let times = cty.lookup("JW5X").unwrap().sun_times(date);
+        assert!(times.sunrise.is_none() && times.sunset.is_none());
+    }
+
+    #[cfg(feature = "timezone")]
+    #[test]
+    fn dx_local_time() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let now = DateTime::parse_from_rfc3339("2023-11-03T18:12:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let local = cty.local_time_for("JA1XYZ", now).unwrap();
+        assert_eq!(
+            local.format("%Y-%m-%d %H:%M").to_string(),
+            "2023-11-04 03:12"
+        );
+        assert_eq!(local, now);
+        assert!(cty.local_time_for("012", now).is_none());
+    }
+
+    #[test]
+    fn long_path() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let path = cty.path("DL1ABC", "VK3XYZ").unwrap();
+        assert!((path.distance + path.long_distance - 40_030.0).abs() < 1.0);
+        assert!((path.long_bearing - (path.bearing + 180.0)).abs() < 1e-9);
+        let path = geo::path(0.0, 0.0, 0.0, -10.0);
+        assert_eq!(path.long_bearing, 90.0);
+    }
+
+    #[cfg(feature = "clublog")]
+    #[test]
+    fn most_wanted_rank() {
+        let mut cty = Cty::new("cty.dat").unwrap();
+        let list = clublog::MostWanted::parse(r#"{"1":"P5","2":"3Y\/B","3":"FT5\/W","120":"BY"}"#)
+            .unwrap();
+        cty.apply_most_wanted(&list);
+        assert_eq!(cty.lookup("P5ABC").unwrap().most_wanted_rank(), Some(1));
+        assert_eq!(cty.lookup("3Y0J").unwrap().most_wanted_rank(), Some(2));
+        assert_eq!(cty.lookup("DL1ABC").unwrap().most_wanted_rank(), None);
+        // Overridden prefixes see the rank too
+        assert_eq!(cty.lookup("B9U").unwrap().most_wanted_rank(), Some(120));
+
+        let list = clublog::MostWanted::parse(
+            "Rank,Prefix,Name\n1,P5,DPR of Korea\n2,BS7,Scarborough Reef\n",
+        )
+        .unwrap();
+        cty.apply_most_wanted(&list);
+        assert_eq!(cty.lookup("P5ABC").unwrap().most_wanted_rank(), Some(1));
+        assert_eq!(cty.lookup("BS7H").unwrap().most_wanted_rank(), Some(2));
+        assert_eq!(cty.lookup("3Y0J").unwrap().most_wanted_rank(), None);
+    }
+
+    #[test]
+    fn lint_country_file() {
+        assert_eq!(lint::lint_file("cty.dat").unwrap(), vec![]);
+
+        let path = std::env::temp_dir().join("cty-rs-lint.dat");
+        std::fs::write(
+            &path,
+            "Spain:  14:  37:  EU:   40.32:     3.43:    -1.0:  EA:\n    EA,EB<91/0>;\n\
+             Canary Islands:  33:  36:  AF:   28.32:   195.20:     0.0:  EA8:\n    EA8,EB;\n\
+             Broken:  14:  xx:  EU:   40.32:     3.43:    -1.0:  XX:\n",
+        )
+        .unwrap();
+        let err = lint::lint_file(path.to_str().unwrap()).unwrap_err();
+        let err = err.downcast::<ParseError>().unwrap();
+        assert_eq!(err.line, 5);
+
+        std::fs::write(
+            &path,
+            "Spain:  14:  37:  EU:   40.32:     3.43:    -1.0:  EA:\n    EA,EB<91/0>;\n\
+             Canary Islands:  33:  36:  AF:   28.32:   195.20:     0.0:  EA8:\n    EA8,EB;\n",
+        )
+        .unwrap();
+        let diagnostics = lint::lint_file(path.to_str().unwrap()).unwrap();
+        let lines = diagnostics.iter().map(|d| d.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![2, 3, 4]);
+        assert!(diagnostics[2].message.contains("line 2"));
+
+        // Zones out of range don't parse
+        let err = lint::lint_str(
+            "Spain:  14:  37:  EU:   40.32:     3.43:    -1.0:  EA:\n    EA,EB(41);\n",
+        )
+        .unwrap_err();
+        let err = err.downcast::<ParseError>().unwrap();
+        assert_eq!(
+            (err.line, err.message.as_str()),
+            (2, "CQ zone 41 out of range 1-40")
+        );
+    }
+
+    #[test]
+    fn database_diff() {
+        let old = Cty::new("cty.dat").unwrap();
+        assert!(old.diff(&old).is_empty());
+
+        let mut new = Cty::new("cty.dat").unwrap();
+        new.update_entities(|e| {
+            if e.prefix == "DL" {
+                e.cq = cq(15);
+            }
+        });
+        let dl = new.prefixes["DL"].clone();
+        new.prefixes.insert(Prefix {
+            pattern: "Q1".to_string(),
+            ..dl
+        });
+        new.prefixes.remove("S6");
+        new.prefixes.get_mut("DA").unwrap().overrides.cq = Some(cq(16));
+
+        let diff = old.diff(&new);
+        assert!(diff.added_entities.is_empty() && diff.removed_entities.is_empty());
+        assert_eq!(diff.changed_entities.len(), 1);
+        assert_eq!(diff.changed_entities[0].1.cq, 15);
+        assert_eq!(diff.added_prefixes.len(), 1);
+        assert_eq!(diff.added_prefixes[0].pattern, "Q1");
+        assert_eq!(diff.removed_prefixes[0].pattern, "S6");
+        assert_eq!(diff.changed_prefixes.len(), 1);
+        assert_eq!(diff.changed_prefixes[0].1.overrides.cq, Some(cq(16)));
+    }
+
+    #[test]
+    fn database_validation() {
+        let mut cty = Cty::new("cty.dat").unwrap();
+        // The primary prefix of Antarctica is an alias of South Shetland Islands
+        assert_eq!(
+            cty.validate(),
+            vec![Issue::ConflictingPrefix {
+                pattern: "CE9".to_string(),
+                entities: vec![
+                    "Antarctica".to_string(),
+                    "South Shetland Islands".to_string()
+                ]
+            }]
+        );
+
+        let dl = cty.prefixes["DL"].clone();
+        let exact = Prefix {
+            pattern: "DL0XX".to_string(),
+            is_exact: true,
+            ..dl.clone()
+        };
+        cty.insert_prefix(exact.clone());
+        cty.insert_prefix(Prefix {
+            is_exact: false,
+            ..exact
+        });
+        cty.insert_prefix(Prefix {
+            pattern: "S6".to_string(),
+            ..dl
+        });
+        let issues = cty.validate();
+        assert!(issues.contains(&Issue::ShadowedExactCall {
+            pattern: "DL0XX".to_string()
+        }));
+        assert!(issues.contains(&Issue::ConflictingPrefix {
+            pattern: "S6".to_string(),
+            entities: vec!["Singapore".to_string(), "Fed. Rep. of Germany".to_string()]
+        }));
+        assert_eq!(issues.len(), 3);
+    }
+
+    #[test]
+    fn lenient_parsing() {
+        let path = std::env::temp_dir().join("cty-rs-lenient.dat");
+        std::fs::write(
+            &path,
+            "Spain:  14:  37:  EU:   40.32:     3.43:    -1.0:  EA:\n    EA,EB(4294967296),EC;\n\
+             Broken:  14:  xx:  EU:   40.32:     3.43:    -1.0:  XX:\n    XX,XY;\n\
+             Canary Islands:  33:  36:  AF:   28.32:    15.20:     0.0:  EA8:\n    EA8;\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+        assert!(Cty::new(path).is_err());
+        assert!(CtyBuilder::new().load(path).is_err());
+
+        let (cty, warnings) = CtyBuilder::new().lenient(true).load(path).unwrap();
+        assert_eq!(cty.entities().count(), 2);
+        assert_eq!(cty.lookup("EC1A").unwrap().name, "Spain");
+        assert_eq!(cty.lookup("EA8A").unwrap().name, "Canary Islands");
+        // The bad prefix and the prefixes of the bad entity are skipped
+        assert!(cty.lookup("EB1A").is_none());
+        assert!(cty.lookup("XY1A").is_none());
+        let lines = warnings.iter().map(|w| w.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![2, 3]);
+    }
+
+    #[test]
+    fn merge_overlay() {
+        let path = std::env::temp_dir().join("cty-rs-overlay.dat");
+        std::fs::write(
+            &path,
+            "Singapore:                28:  54:  AS:    1.37:  -103.78:    -8.0:  9V:\n    =DL1ABC;\n\
+             Test Island:  40:  90:  OC:  1.0:  1.0:  0.0:  Q1:\n    Q1,=DL2ABC;\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        let mut cty = Cty::new("cty.dat").unwrap();
+        let entities = cty.entities().count();
+        let in_zone = cty.entities_in_itu_zone(itu(90)).count();
+        cty.merge(Cty::new(path).unwrap());
+        assert_eq!(cty.entities().count(), entities + 1);
+        assert_eq!(cty.lookup("DL1ABC").unwrap().name, "Singapore");
+        assert_eq!(cty.lookup("DL2ABC").unwrap().name, "Test Island");
+        assert_eq!(cty.lookup("DL3ABC").unwrap().name, "Fed. Rep. of Germany");
+        // Prefixes of the base file still resolve to the replaced entity record
+        assert_eq!(cty.lookup("S6ABC").unwrap().name, "Singapore");
+        assert_eq!(cty.entities_in_itu_zone(itu(90)).count(), in_zone + 1);
+
+        let (built, _) = CtyBuilder::new()
+            .with_file("cty.dat")
+            .with_file(path)
+            .build()
+            .unwrap();
+        assert_eq!(built.lookup("DL1ABC").unwrap().name, "Singapore");
+        let (loaded, _) = CtyBuilder::new().with_file(path).load("cty.dat").unwrap();
+        assert_eq!(loaded.lookup("DL2ABC").unwrap().name, "Test Island");
+    }
+
+    #[test]
+    fn override_tokens() {
+        let data = "Germany:  14:  28:  EU:  51.00:  -10.00:  -1.0:  DL:\n    DL,=DL0ABC(15)[29]<52.5/-13.4>{AS}~-2.0~,DA<1/2>;\n";
+        let cty: Cty = data.parse().unwrap();
+        let entity = cty.lookup("DL0ABC").unwrap();
+        assert_eq!((entity.cq, entity.itu), (cq(15), itu(29)));
+        assert_eq!((entity.lat, entity.lon), (52.5, -13.4));
+        assert_eq!(entity.continent, Continent::AS);
+        assert_eq!(entity.utc_offset(), 7200);
+        assert_eq!(cty.lookup_prefix("DA1A").unwrap().pattern, "DA");
+        assert!(data.replace("(15)", "(15").parse::<Cty>().is_err());
+        assert!(data.replace("<1/2>", "<1>").parse::<Cty>().is_err());
+    }
+
+    #[test]
+    fn wpx_prefixes() {
+        assert_eq!(wpx_prefix("DL1ABC").unwrap(), "DL1");
+        assert_eq!(wpx_prefix("hg19abc").unwrap(), "HG19");
+        assert_eq!(wpx_prefix("2E0ABC").unwrap(), "2E0");
+        assert_eq!(wpx_prefix("RAEM").unwrap(), "RA0");
+        assert_eq!(wpx_prefix("VP2E/K1AA").unwrap(), "VP2E");
+        assert_eq!(wpx_prefix("K1AA/7").unwrap(), "K7");
+        assert_eq!(wpx_prefix("N8BJQ/PA").unwrap(), "PA0");
+        assert_eq!(wpx_prefix("DL1ABC/P").unwrap(), "DL1");
+        assert_eq!(wpx_prefix("OE25AA/MM").unwrap(), "OE25");
+        assert!(wpx_prefix("").is_none());
+        assert!(wpx_prefix("123").is_none());
+        assert!(wpx_prefix("AÄ").is_none());
+        assert!(wpx_prefix("AÄ/P").is_none());
+        assert!(wpx_prefix("DL1ÄBC/7").is_none());
+    }
+
+    #[cfg(feature = "adif")]
+    #[test]
+    fn adif_enrichment() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let log = "Exported log\n<ADIF_VER:5>3.1.4 <EOH>\n\
+            <CALL:6>DL1ABC <BAND:3>20m <CQZ:2>05 <DXCC:3>230 <EOR>\n\
+            <call:6>IT9ABC <cont:2>eu <EOR>\n\
+            <CALL:4>Q1AA <EOR>\n";
+        let mut adif = adif::Adif::parse(log).unwrap();
+        assert_eq!(adif.records.len(), 3);
+        let enrichment = cty.enrich_adif(&mut adif);
+        assert_eq!(enrichment.unresolved, vec![2]);
+        let fields = |record: usize| {
+            enrichment
+                .corrections
+                .iter()
+                .filter(|c| c.record == record)
+                .map(|c| c.field)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(fields(0), vec!["CQZ", "ITUZ", "CONT", "COUNTRY"]);
+        assert_eq!(fields(1), vec!["DXCC", "CQZ", "ITUZ", "COUNTRY"]);
+        assert_eq!(
+            enrichment.corrections[0].to_string(),
+            "QSO 1 (DL1ABC): CQZ 05 -> 14"
+        );
+        // Sicily is WAE-only, its DXCC entity is Italy
+        assert_eq!(adif.records[1].get("COUNTRY"), Some("ITALY"));
+        assert_eq!(
+            adif.records[0].get("COUNTRY"),
+            Some("FEDERAL REPUBLIC OF GERMANY")
+        );
+
+        let written = adif.to_string();
+        assert!(written.starts_with("Exported log\n<ADIF_VER:5>3.1.4 <EOH>\n<CALL:6>DL1ABC"));
+        assert_eq!(adif::Adif::parse(&written).unwrap(), adif);
+        let fields_only =
+            adif::Adif::parse("<ADIF_VER:5>3.1.4 <EOH> <CALL:6>DL1ABC <EOR>").unwrap();
+        assert_eq!(fields_only.records[0].fields.len(), 1);
+        assert!(adif::Adif::parse("<CALL:10>DL1ABC <EOR>").is_err());
+        assert!(adif::Adif::parse("<CALL:6>DL1ABC").is_err());
+    }
+
+    #[cfg(feature = "cabrillo")]
+    #[test]
+    fn cabrillo_multipliers() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let log = cabrillo::Log::parse(
+            "START-OF-LOG: 3.0\n\
+             CALLSIGN: 9V1AAA\n\
+             CONTEST: CQ-WW-CW\n\
+             QSO:  7005 CW 2023-11-25 0000 9V1AAA 599 28 DL1ABC 599 14\n\
+             QSO: 14025 CW 2023-11-25 0001 9V1AAA 599 28 DL2XYZ 599 14\n\
+             QSO: 14026 CW 2023-11-25 0002 9V1AAA 599 28 JA1XYZ 599 25\n\
+             QSO:    50 CW 2023-11-25 0003 9V1AAA 599 28 Q1AA 599 1 1\n\
+             END-OF-LOG:\n",
+        )
+        .unwrap();
+        assert_eq!(log.header("callsign"), Some("9V1AAA"));
+        assert_eq!(log.qsos[0].received, vec!["599", "14"]);
+        assert_eq!(log.qsos[3].transmitter.as_deref(), Some("1"));
+
+        let bands = cty.multipliers(&log);
+        let names = bands.iter().map(|b| b.band.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["40m", "20m", "6m"]);
+        assert_eq!(bands[1].qsos, 2);
+        assert_eq!(bands[1].entities.len(), 2);
+        assert_eq!(
+            bands[1].cq_zones.iter().copied().collect::<Vec<_>>(),
+            vec![14, 25]
+        );
+        assert_eq!(bands[1].continents.len(), 2);
+        assert_eq!(bands[2].unresolved, vec!["Q1AA"]);
+        assert!(cabrillo::Log::parse("QSO: 14025 CW\n").is_err());
+    }
+
+    #[test]
+    fn release_version() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.version(), Some("20231103"));
+        #[cfg(feature = "timezone")]
+        assert_eq!(
+            cty.release_date(),
+            chrono::NaiveDate::from_ymd_opt(2023, 11, 3)
+        );
+        let cty: Cty = "Singapore:  28:  54:  AS:  1.37:  -103.78:  -8.0:  9V:\n    9V,=VERSION;\n"
+            .parse()
+            .unwrap();
+        assert!(cty.version().is_none());
+    }
+
+    #[test]
+    fn sorted_iteration() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let names = cty
+            .entities_sorted_by_name()
+            .map(|e| e.name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names.len(), 346);
+        assert_eq!(names[0], "Afghanistan");
+        assert!(names.windows(2).all(|w| w[0] <= w[1]));
+        let patterns = cty
+            .prefixes_sorted()
+            .map(|p| p.pattern.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(patterns.len(), cty.prefixes().count());
+        assert!(patterns.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn entity_summary() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(
+            cty.lookup("DL1ABC").unwrap().summary(),
+            "Fed. Rep. of Germany (DL) — CQ 14, ITU 28, EU, 51.0N 10.0E, UTC+1"
+        );
+        assert_eq!(
+            cty.lookup("VU2ABC").unwrap().to_string(),
+            "India (VU) — CQ 22, ITU 41, AS, 22.5N 77.6E, UTC+5:30"
+        );
+        assert!(cty
+            .lookup("W1AW")
+            .unwrap()
+            .to_string()
+            .ends_with("W, UTC-5"));
+    }
+
+    #[test]
+    fn nearest_entity() {
+        let cty = Cty::new("cty.dat").unwrap();
+        // Frankfurt
+        assert_eq!(cty.nearest_entity(50.1, 8.7).unwrap().prefix, "DL");
+        // Across the date line from Fiji, 179.9W is close to 178E
+        assert_eq!(cty.nearest_entity(-17.8, -179.9).unwrap().prefix, "3D2");
+        assert!(Cty::default().nearest_entity(0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn deleted_entities() {
+        let mut cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.deleted_entities().count(), 0);
+        assert_eq!(deleted_dxcc_name(81), Some("Germany"));
+        assert_eq!(deleted_dxcc_name(230), None);
+        // Every code in the file is a current entity
+        assert!(cty.entities().all(|e| deleted_dxcc_name(e.dxcc).is_none()));
+
+        cty.update_entities(|e| e.deleted = e.prefix == "DL");
+        let deleted = cty.deleted_entities().collect::<Vec<_>>();
+        assert_eq!(deleted.len(), 1);
+        assert!(cty.lookup("DL1ABC").unwrap().deleted);
+    }
+
+    #[test]
+    fn special_case_rules() {
+        let mut cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.lookup("KG4AB").unwrap().name, "Guantanamo Bay");
+        assert_eq!(cty.lookup("KG4ABC").unwrap().prefix, "K");
+        assert_eq!(cty.lookup("KG4ABC/P").unwrap().prefix, "K");
+        assert_eq!(cty.lookup("KG4A").unwrap().prefix, "K");
+        // Exact calls keep their zones
+        assert_eq!(cty.lookup("KG4BIG").unwrap().cq, 4);
+
+        cty.add_rule(Rule::custom(|call, entity| {
+            (entity.prefix == "DL" && call.ends_with("XX")).then(|| "OE".to_string())
+        }));
+        assert_eq!(cty.lookup("DL1XX").unwrap().name, "Austria");
+        assert_eq!(cty.lookup("DL1AB").unwrap().prefix, "DL");
+
+        cty.clear_rules();
+        assert_eq!(cty.lookup("KG4ABC").unwrap().name, "Guantanamo Bay");
+    }
+
+    #[test]
+    fn relocated_calls() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.lookup("W1ABC/KH6").unwrap().name, "Hawaii");
+        assert_eq!(cty.lookup("KH6/W1ABC").unwrap().name, "Hawaii");
+        assert_eq!(cty.lookup("W1ABC/KH6/P").unwrap().name, "Hawaii");
+        assert_eq!(cty.lookup("UA3XYZ").unwrap().name, "European Russia");
+        let asiatic = cty.lookup("UA3XYZ/0").unwrap();
+        assert_eq!(asiatic.name, "Asiatic Russia");
+        assert_eq!(asiatic, cty.lookup("UA0XYZ").unwrap());
+        // A KG4 designator is Guantanamo Bay whatever its length
+        assert_eq!(cty.lookup("W1ABC/KG4").unwrap().name, "Guantanamo Bay");
+        assert_eq!(cty.lookup("KG4ABC/P").unwrap().prefix, "K");
+        assert_eq!(cty.lookup("DL1ABC/P").unwrap().prefix, "DL");
+        assert_eq!(cty.lookup("AÄ/1"), None);
+        assert_eq!(cty.lookup("1/AÄ"), None);
+        assert_eq!(cty.lookup("DLÄ"), None);
+    }
+
+    #[test]
+    fn database_stats() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let stats = cty.stats();
+        assert_eq!(stats.entities, 346);
+        assert_eq!(stats.prefixes + stats.exact_calls, cty.prefixes().count());
+        assert_eq!(stats.by_continent.values().sum::<usize>(), 346);
+        assert_eq!(stats.missing_cq_zones().count(), 0);
+        assert!(stats.to_string().starts_with("346 entities, "));
+    }
+
+    #[cfg(feature = "mmap")]
+    #[test]
+    fn mmap_loading() {
+        let cty = Cty::load_mmap("cty.dat").unwrap();
+        assert_eq!(cty.stats(), Cty::new("cty.dat").unwrap().stats());
+        assert_eq!(cty.lookup("DL1ABC").unwrap().name, "Fed. Rep. of Germany");
+
+        let file = MappedFile::open("cty.dat").unwrap();
+        assert!(lint::lint_str(file.as_str().unwrap()).is_ok());
+        assert!(MappedFile::open("missing.dat").is_err());
+    }
+
+    #[test]
+    fn builder_options() {
+        let data = "Testland: 1: 2: EU: 50.0: 10.0: -1.0: T0:\n    T0,T1<51.0/11.0>,=T0ABC;\n";
+        let (cty, _) = CtyBuilder::new()
+            .longitude(Longitude::East)
+            .parse(data)
+            .unwrap();
+        assert_eq!(cty.lookup("T0A").unwrap().coordinates(), (50.0, 10.0));
+        assert_eq!(cty.lookup("T1A").unwrap().coordinates(), (51.0, 11.0));
+        assert!(cty.lookup(" t0abc").is_none());
+
+        let (cty, _) = CtyBuilder::new().normalize_calls(true).parse(data).unwrap();
+        assert_eq!(cty.lookup(" t0abc\n").unwrap().name, "Testland");
+
+        let cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.lookup("W1ABC/KH6").unwrap().name, "Hawaii");
+        for (portable, hawaii) in [
+            (PortableCalls::HomeCall, false),
+            (PortableCalls::Prefix, true),
+        ] {
+            let (cty, _) = CtyBuilder::new()
+                .portable_calls(portable)
+                .load("cty.dat")
+                .unwrap();
+            assert_eq!(cty.lookup("W1ABC/KH6").unwrap().prefix, "K");
+            assert_eq!(cty.lookup("UA3XYZ/0").unwrap().name, "European Russia");
+            assert_eq!(cty.lookup("KH6/W1ABC").unwrap().name == "Hawaii", hawaii);
+            assert_eq!(cty.lookup("KG4ABC/P").unwrap().prefix, "K");
+        }
+    }
+
+    #[cfg(feature = "service")]
+    #[test]
+    fn service_refresh() {
+        use std::{
+            sync::{Arc, Mutex},
+            time::Duration,
+        };
+
+        let header = "Singapore:  28:  54:  AS:  1.37:  -103.78:  -8.0:  9V:\n";
+        let data = Arc::new(Mutex::new(format!("{header}    9V;\n")));
+        let source = data.clone();
+        let service = CtyService::with_loader(
+            move || source.lock().unwrap().parse(),
+            Some(Duration::from_millis(10)),
+        )
+        .unwrap();
+        let handle = service.clone();
+        assert!(handle.lookup("S6ABC").is_none());
+
+        *data.lock().unwrap() = format!("{header}    9V,S6;\n");
+        let refreshed = (0..300).any(|_| {
+            std::thread::sleep(Duration::from_millis(10));
+            handle.lookup("S6ABC").is_some()
+        });
+        assert!(refreshed);
+
+        // Failed loads keep the data
+        *data.lock().unwrap() = "garbage".to_string();
+        assert!(service.reload().is_err());
+        assert!(handle.lookup("S6ABC").is_some());
+
+        let fixed = CtyService::new(Cty::default());
+        assert!(fixed.reload().is_err());
+        fixed.replace(format!("{header}    9V;\n").parse().unwrap());
+        assert_eq!(fixed.current().entities().count(), 1);
+    }
+
+    #[test]
+    fn chained_resolvers() {
+        let cty = Arc::new(Cty::new("cty.dat").unwrap());
+        let club = Arc::new(Entity {
+            name: "Club station".to_string(),
+            ..Entity::default()
+        });
+        let exceptions = move |call: &str| (call == "DL0ABC").then(|| club.clone());
+        let chain = ChainedResolver::new().with(exceptions).with(cty.clone());
+        assert_eq!(chain.len(), 2);
+        assert_eq!(chain.resolve("DL0ABC").unwrap().name, "Club station");
+        assert_eq!(
+            chain.resolve("DL1ABC").unwrap().name,
+            "Fed. Rep. of Germany"
+        );
+        assert_eq!(chain.resolve("DL1ABC"), cty.resolve("DL1ABC"));
+        assert!(chain.resolve("1234").is_none());
+        assert!(ChainedResolver::new().resolve("DL1ABC").is_none());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_export() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let json: serde_json::Value = serde_json::from_str(&cty.to_json()).unwrap();
+        assert_eq!(json["version"], "20231103");
+        assert_eq!(json["entities"].as_array().unwrap().len(), 346);
+        let prefixes = json["prefixes"].as_array().unwrap();
+        assert_eq!(prefixes.len(), cty.prefixes().count());
+        let prefix = |pattern: &str| {
+            prefixes
+                .iter()
+                .find(|p| p["pattern"] == pattern)
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(
+            prefix("DL"),
+            serde_json::json!({"pattern": "DL", "exact": false, "entity": "DL"})
+        );
+        assert_eq!(prefix("KG4BIG")["exact"], true);
+        assert_eq!(prefix("KG4BIG")["overrides"]["cq"], 4);
+    }
+
+    #[test]
+    fn encodings() {
+        let data =
+            "\u{feff}Cura\u{e7}ao:  9:  11:  SA:  12.17:  69.00:  4.0:  PJ2:\r\n    PJ2;\r\n";
+        let (cty, _) = CtyBuilder::new().parse(data).unwrap();
+        assert_eq!(cty.lookup("PJ2T").unwrap().name, "Cura\u{e7}ao");
+        assert_eq!(cty.lookup("PJ2T").unwrap().prefix, "PJ2");
+        let (cty, _) = CtyBuilder::new().parse_bytes(data.as_bytes()).unwrap();
+        assert_eq!(cty.lookup("PJ2T").unwrap().name, "Cura\u{e7}ao");
+
+        let latin1 = b"Cura\xe7ao:  9:  11:  SA:  12.17:  69.00:  4.0:  PJ2:\n    PJ2;\n";
+        let parsed = CtyBuilder::new().parse_bytes(latin1);
+        #[cfg(feature = "encoding")]
+        assert_eq!(
+            parsed.unwrap().0.lookup("PJ2T").unwrap().name,
+            "Cura\u{e7}ao"
+        );
+        #[cfg(not(feature = "encoding"))]
+        assert!(parsed.is_err());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn load_from_url() {
+        use std::{io::Read, io::Write, net::TcpListener, time::Duration};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/cty.dat", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let body = "Singapore:  28:  54:  AS:  1.37:  -103.78:  -8.0:  9V:\n    9V,S6;\n";
+            for (i, stream) in listener.incoming().enumerate() {
+                let mut stream = stream.unwrap();
+                let mut request = [0; 1024];
+                let _ = stream.read(&mut request);
+                // The third request never gets an answer
+                if i == 2 {
+                    std::thread::sleep(Duration::from_secs(2));
+                    continue;
+                }
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+
+        let cty = Cty::load_url(&url).unwrap();
+        assert_eq!(cty.lookup("S6ABC").unwrap().name, "Singapore");
+        assert!(CtyBuilder::new()
+            .max_download_size(10)
+            .load_url(&url)
+            .is_err());
+        let slow = CtyBuilder::new()
+            .http_timeout(Duration::from_millis(200))
+            .load_url(&url);
+        assert!(slow.is_err());
+    }
+
+    #[cfg(feature = "timezone")]
+    #[test]
+    fn release_age() {
+        use std::time::Duration;
+
+        let cty = Cty::new("cty.dat").unwrap();
+        // Released 2023-11-03
+        assert!(cty.age().unwrap() > Duration::from_secs(86_400 * 365));
+        assert!(cty.is_outdated(Duration::from_secs(86_400 * 30)));
+        assert!(!cty.is_outdated(Duration::from_secs(86_400 * 365 * 100)));
+        assert!(Cty::default().age().is_none());
+        assert!(Cty::default().is_outdated(Duration::MAX));
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn update_check() {
+        use std::{io::Read, io::Write, net::TcpListener};
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/cty.dat", listener.local_addr().unwrap());
+        let header = "Singapore:  28:  54:  AS:  1.37:  -103.78:  -8.0:  9V:\n";
+        let body = format!("{header}    9V,S6,=VER20240101;\n");
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let _ = stream.read(&mut [0; 1024]);
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+
+        let old: Cty = format!("{header}    9V,=VER20231103;\n").parse().unwrap();
+        let update = old.check_for_update(&url).unwrap().unwrap();
+        assert_eq!(update.version.as_deref(), Some("20240101"));
+        let changes = update.changes(&old);
+        assert_eq!(changes.added_prefixes.len(), 2);
+        assert!(update.cty.check_for_update(&url).unwrap().is_none());
+    }
+
+    #[test]
+    fn entity_aliases() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let italy = cty.lookup("I1ABC").unwrap();
+        assert_eq!(italy.primary_prefix(), "I");
+        let aliases = cty.aliases_of(italy).collect::<Vec<_>>();
+        assert!(aliases.iter().any(|p| p.pattern == "4U" && !p.is_exact));
+        assert!(aliases
+            .iter()
+            .all(|p| cty.lookup_prefix(&p.pattern).is_some()));
+        assert!(aliases.windows(2).all(|w| w[0].pattern < w[1].pattern));
+        // Sicily is a WAE entity of its own
+        assert!(!aliases.iter().any(|p| p.pattern == "IT9"));
+        // Prefixes with overrides resolve to copies that still count
+        let guantanamo = cty.lookup("KG4AB").unwrap();
+        assert!(cty.aliases_of(guantanamo).any(|p| p.pattern == "KG4"));
+    }
+
+    #[test]
+    fn lookup_explanation() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let explanation = cty.explain("EA8/DL1ABC/P");
+        assert_eq!(explanation.entity.unwrap().name, "Canary Islands");
+        assert_eq!(explanation.entity, cty.lookup("EA8/DL1ABC/P"));
+        let steps = explanation
+            .steps
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            [
+                "home call DL1ABC, designator EA8, ignoring /P",
+                "looking up the designator EA8",
+                "matched prefix EA8 of Canary Islands"
+            ]
+        );
+
+        let explanation = cty.explain("KG4ABC");
+        assert!(
+            matches!(explanation.steps.last(), Some(Step::Rule { entity, .. }) if entity.prefix == "K")
+        );
+        assert!(explanation
+            .to_string()
+            .ends_with("=> United States (K) — CQ 5, ITU 8, NA, 37.6N 91.9W, UTC-5"));
+        assert!(cty
+            .explain("DL1XYZ")
+            .steps
+            .iter()
+            .any(|s| matches!(s, Step::Tried(p) if p == "DL1XYZ")));
+        assert!(cty.explain("Ä").entity.is_none());
+    }
+
+    #[test]
+    fn suggestions() {
+        let cty = Cty::new("cty.dat").unwrap();
+        assert!(cty.lookup("QL1ABC").is_none());
+        let suggestions = cty.suggest("ql1abc", 5);
+        assert_eq!(suggestions.len(), 5);
+        assert!(suggestions.iter().all(|&(_, distance)| distance == 1));
+        assert!(suggestions
+            .windows(2)
+            .all(|w| w[0].0.pattern.len() >= w[1].0.pattern.len()));
+
+        // A busted exact call
+        let (prefix, distance) = cty.suggest("KG4BIH", 1)[0];
+        assert_eq!((prefix.pattern.as_str(), distance), ("KG4", 0));
+        assert!(cty
+            .suggest("KG4BIH", 10)
+            .iter()
+            .any(|&(p, d)| p.pattern == "KG4BIG" && p.is_exact && d == 1));
+        assert!(cty
+            .suggest("", 3)
+            .iter()
+            .all(|&(p, d)| d == p.pattern.len()));
+    }
+
+    #[test]
+    fn plausible_callsigns() {
+        for call in [
+            "DL1ABC",
+            "dl1abc",
+            "W1ABC/KH6",
+            "UA3XYZ/0",
+            "EA8/DL1ABC/P",
+            "2E0ABC",
+            "3DA0XYZ",
+            "4U1ITU",
+            "K1A",
+            "GB13COL",
+            "VP2E/K1AA",
+            "N8BJQ/MM",
+        ] {
+            assert!(is_plausible_callsign(call), "{call}");
+        }
+        for call in [
+            "",
+            "CQ",
+            "TEST",
+            "599",
+            "DL1ABC/",
+            "/DL1ABC",
+            "DL/1ABC",
+            "DL1-ABC",
+            "ABCD1XYZ",
+            "DL1ABCDE",
+            "DL12345A",
+            "KH6/W1ABC/EA8",
+        ] {
+            assert!(!is_plausible_callsign(call), "{call}");
+        }
+    }
+
+    #[test]
+    fn call_area_regions() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let region = cty.region("W6ABC").unwrap();
+        assert_eq!(
+            (region.call_area, region.name),
+            (Some(6), Some("California"))
+        );
+        assert!(!region.zones_adjusted);
+        assert_eq!(cty.region("K1AA/4").unwrap().name, Some("Southeast"));
+        assert_eq!(cty.region("VO1AA").unwrap().name, Some("Newfoundland"));
+        assert_eq!(cty.region("VA3XYZ").unwrap().name, Some("Ontario"));
+        // VE2 is in ITU zone 4 by prefix override
+        let quebec = cty.region("VE2ABC").unwrap();
+        assert_eq!(
+            (quebec.cq, quebec.itu, quebec.zones_adjusted),
+            (cq(5), itu(4), true)
+        );
+        let germany = cty.region("DL1ABC").unwrap();
+        assert_eq!((germany.call_area, germany.name), (Some(1), None));
+        assert_eq!(cty.region("UA3ABC").unwrap().name, Some("Central"));
+        assert_eq!(cty.region("RA1ABC").unwrap().name, Some("Northwest"));
+        assert_eq!(cty.region("R9FA").unwrap().name, Some("Perm and Komi"));
+        assert_eq!(
+            cty.region("UA9ABC").unwrap().name,
+            Some("Urals and Siberia")
+        );
+        let relocated = cty.region("UA3XYZ/0").unwrap();
+        assert_eq!(relocated.name, Some("East Siberia and Far East"));
+        // Kaliningrad is an entity of its own
+        assert_eq!(cty.region("UA2ABC").unwrap().name, None);
+        assert!(cty.region("QQ1ABC").is_none());
+    }
+
+    #[test]
+    fn callsign_parts() {
+        let call: Callsign = "ea8/dl1abc/p".parse().unwrap();
+        assert_eq!(call.prefix_designator.as_deref(), Some("EA8"));
+        assert_eq!(call.base_call, "DL1ABC");
+        assert_eq!(call.suffixes, ["P"]);
+        assert_eq!(call.location(), Some("EA8"));
+        assert_eq!(call.modifiers().collect::<Vec<_>>(), ["P"]);
+        assert_eq!(call.to_string(), "EA8/DL1ABC/P");
+        assert_eq!(call.wpx_prefix().as_deref(), Some("EA8"));
+
+        let call = Callsign::parse("W1ABC/KH6").unwrap();
+        assert_eq!(
+            (call.prefix_designator.as_deref(), call.location()),
+            (None, Some("KH6"))
+        );
+        let call = Callsign::parse("K1AA/7/QRP").unwrap();
+        assert_eq!(call.wpx_prefix().as_deref(), Some("K7"));
+        assert!(call.is_portable());
+        let call = Callsign::parse("DL1ABC").unwrap();
+        assert!(!call.is_portable() && call.location().is_none());
+        assert!(Callsign::parse("TEST").is_err());
+    }
+
+    #[test]
+    fn multiplier_tracking() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let mut tracker = MultiplierTracker::new();
+        let dl = cty.lookup("DL1ABC").unwrap();
+        assert!(tracker.check("20m", dl).entity);
+        assert!(tracker.add("20m", dl).any());
+        assert!(!tracker.check("20m", dl).any());
+        assert!(tracker.check("40m", dl).entity);
+
+        // New entity and CQ zone on an already worked continent
+        let new = tracker.add("20m", cty.lookup("OE1ABC").unwrap());
+        assert_eq!(
+            new,
+            NewMultipliers {
+                entity: true,
+                cq_zone: true,
+                ..NewMultipliers::default()
+            }
+        );
+        tracker.add_unresolved("20m", "QQ1ABC");
+        let band = tracker.band("20m").unwrap();
+        assert_eq!(band.qsos, 3);
+        assert_eq!(band.entities.len(), 2);
+        assert_eq!(band.unresolved, ["QQ1ABC"]);
+        assert_eq!(tracker.bands().count(), 1);
+    }
+
+    #[cfg(feature = "exceptions")]
+    #[test]
+    fn exception_overlay() {
+        let path = std::env::temp_dir().join("cty-rs-exceptions.toml");
+        std::fs::write(
+            &path,
+            "[[exception]]\ncall = \"dl1xyz\"\nentity = \"KH6\"\n\n\
+             [[exception]]\nprefix = \"VE2X\"\ncq = 2\nutc_offset = -4.0\n",
+        )
+        .unwrap();
+        let builder = CtyBuilder::new().with_exceptions(path.to_str().unwrap());
+        let (cty, _) = builder.load("cty.dat").unwrap();
+        assert_eq!(cty.lookup("DL1XYZ").unwrap().name, "Hawaii");
+        #[cfg(feature = "mmap")]
+        {
+            let (mapped, _) = builder.load_mmap("cty.dat").unwrap();
+            assert_eq!(mapped.lookup("DL1XYZ").unwrap().name, "Hawaii");
+        }
+        assert_eq!(cty.lookup("DL1XYY").unwrap().name, "Fed. Rep. of Germany");
+        let ve2 = cty.lookup("VE2XAA").unwrap();
+        assert_eq!((ve2.name.as_str(), ve2.cq), ("Canada", cq(2)));
+        assert_eq!(ve2.utc_offset(), -4 * 3600);
+
+        // Portable calls without an entity stay where a lookup puts them
+        let mut cty = Cty::new("cty.dat").unwrap();
+        let json = r#"{"exception": [{"call": "W1ABC/KH6", "cq": 31}]}"#;
+        cty.apply_exceptions(&Exceptions::from_json(json).unwrap())
+            .unwrap();
+        let portable = cty.lookup("W1ABC/KH6").unwrap();
+        assert_eq!((portable.name.as_str(), portable.cq), ("Hawaii", cq(31)));
+
+        let json = r#"{"exception": [{"call": "W1AW", "entity": "Q9"}]}"#;
+        let err = cty.apply_exceptions(&Exceptions::from_json(json).unwrap());
+        assert!(err.unwrap_err().to_string().contains("Q9"));
+        assert!(Exceptions::from_json(r#"{"exception": [{"cal": "W1AW"}]}"#).is_err());
+        let both = Exceptions::from_json(r#"{"exception": [{"call": "W1AW", "prefix": "W"}]}"#);
+        assert!(cty.apply_exceptions(&both.unwrap()).is_err());
+    }
+
+    #[cfg(feature = "timezone")]
+    #[test]
+    fn sun_position() {
+        use chrono::TimeZone;
+
+        let cty = Cty::new("cty.dat").unwrap();
+        let singapore = cty.lookup("9V1AAA").unwrap();
+        // Close to overhead at local noon near the equinox
+        let noon = Utc.with_ymd_and_hms(2024, 3, 20, 5, 0, 0).unwrap();
+        let position = singapore.sun_position(noon);
+        assert!(position.elevation > 85.0, "{position:?}");
+        // Rises in the east and sets in the west
+        let morning = singapore.sun_position(Utc.with_ymd_and_hms(2024, 3, 20, 0, 0, 0).unwrap());
+        assert!(morning.is_daylight() && (80.0..100.0).contains(&morning.azimuth));
+        let evening = singapore.sun_position(Utc.with_ymd_and_hms(2024, 3, 20, 11, 0, 0).unwrap());
+        assert!(evening.is_daylight() && (260.0..280.0).contains(&evening.azimuth));
+        let night = singapore.sun_position(Utc.with_ymd_and_hms(2024, 3, 20, 17, 0, 0).unwrap());
+        assert!(!night.is_daylight());
+
+        // Agrees with the sunrise equation
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let germany = cty.lookup("DL1ABC").unwrap();
+        let sunrise = germany.sun_times(date).sunrise.unwrap();
+        let elevation = germany.sun_position(sunrise).elevation;
+        assert!(
+            (elevation - sun::SUNRISE_ALTITUDE).abs() < 0.5,
+            "{elevation}"
+        );
+    }
+
+    #[test]
+    fn iaru_regions() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let region = |call| cty.lookup(call).unwrap().iaru_region();
+        for (call, expected) in [
+            ("DL1ABC", 1),
+            ("ZS1ABC", 1),
+            ("4X1AB", 1),
+            ("UA0ABC", 1),
+            ("JT1ABC", 3),
+            ("JT2ABC", 3),
+            ("W1AW", 2),
+            ("PY1AB", 2),
+            ("KH6ABC", 2),
+            ("VU2ABC", 3),
+            ("JA1ABC", 3),
+            ("VK2ABC", 3),
+            ("T32AB", 3),
+        ] {
+            assert_eq!(region(call), Some(expected), "{call}");
+        }
+    }
+
+    #[test]
+    fn entity_ids() {
+        use std::collections::HashSet;
+
+        let cty = Cty::new("cty.dat").unwrap();
+        let ids = cty.entities().map(Entity::id).collect::<HashSet<_>>();
+        assert_eq!(ids.len(), cty.entities().count());
+        assert_eq!(cty.lookup("DL1ABC").unwrap().id(), EntityId(230));
+        // WAE-only entities don't share the id of their DXCC entity
+        let sicily = cty.lookup("IT9ABC").unwrap();
+        assert_eq!(sicily.dxcc, cty.lookup("I1ABC").unwrap().dxcc);
+        assert_eq!(sicily.id(), EntityId(1004));
+
+        let worked = ["DL1ABC", "DL2XYZ", "IT9ABC", "I1ABC"]
+            .into_iter()
+            .map(|call| cty.lookup(call).unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(worked.len(), 3);
+        // VE2 overrides the ITU zone of Canada and =AA0Y both zones of the United
+        // States, the resolved records are still the same entities
+        assert_ne!(
+            cty.lookup("VE2ABC").unwrap().itu,
+            cty.lookup("VE3ABC").unwrap().itu
+        );
+        assert_ne!(
+            cty.lookup("AA0Y").unwrap().cq,
+            cty.lookup("W1AW").unwrap().cq
+        );
+        let worked = ["VE2ABC", "VE3ABC", "AA0Y", "W1AW"]
+            .into_iter()
+            .map(|call| cty.lookup(call).unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(worked.len(), 2);
+
+        let overlay = "Test Island:  40:  90:  OC:  1.0:  1.0:  0.0:  Q1:\n    Q1;\n";
+        let mut merged = Cty::new("cty.dat").unwrap();
+        merged.merge(overlay.parse().unwrap());
+        let test = merged.lookup("Q1A").unwrap();
+        assert_eq!(test.id(), EntityId(0x8000 | 346));
+    }
+
+    #[test]
+    fn unknown_continents() {
+        let data = "Test Island:  40:  90:  XX:  1.0:  1.0:  0.0:  Q1:\n    Q1,Q2{EU};\n";
+        assert!(data.parse::<Cty>().is_err());
+
+        let (cty, warnings) = CtyBuilder::new().lenient(true).parse(data).unwrap();
+        assert_eq!(warnings, vec![]);
+        assert_eq!(
+            cty.lookup("Q1A").unwrap().continent,
+            Continent::Unknown("XX".to_string())
+        );
+        assert_eq!(cty.lookup("Q2A").unwrap().continent, Continent::EU);
+        assert_eq!(cty.lookup("Q1A").unwrap().iaru_region(), None);
+        assert!(cty.validate().contains(&Issue::UnknownContinent {
+            pattern: "Q1".to_string(),
+            continent: "XX".to_string(),
+        }));
+    }
+
+    #[test]
+    fn raw_override_tokens() {
+        let data = "Germany:  14:  28:  EU:  51.00:  -10.00:  -1.0:  DL:\n    DL,DA(15)[29],=DL0ABC<52.5/-13.4>~-2.0~;\n";
+        let mut cty: Cty = data.parse().unwrap();
+        assert_eq!(cty.prefixes["DA"].override_tokens(), "(15)[29]");
+        assert_eq!(
+            cty.prefixes["DL0ABC"].override_tokens(),
+            "<52.5/-13.4>~-2.0~"
+        );
+        assert_eq!(cty.prefixes["DL"].override_tokens(), "");
+
+        // Kept when the entity records are replaced
+        cty.update_entities(|e| e.name = "Germany (updated)".to_string());
+        let da = cty.lookup_prefix("DA1ABC").unwrap();
+        assert_eq!(da.override_tokens(), "(15)[29]");
+        assert_eq!(da.resolved().name, "Germany (updated)");
+    }
+
+    #[test]
+    fn all_parse_errors() {
+        let data = "Spain:  14:  37:  EU:   40.32:     3.43:    -1.0:  EA:\n    EA,EB(41),EC[0];\n\
+                    Broken:  14:  xx:  EU:   40.32:     3.43:    -1.0:  XX:\n    XX;\n\
+                    Canary Islands:  33:  36:  AF:   28.32:    15.20:     0.0:  EA8:\n    EA8<1>;\n";
+        let err = CtyBuilder::new().all_errors(true).parse(data).unwrap_err();
+        let errors = err.downcast::<ParseErrors>().unwrap().errors;
+        let lines = errors.iter().map(|e| e.line).collect::<Vec<_>>();
+        assert_eq!(lines, vec![2, 2, 3, 6]);
+        assert_eq!(errors[0].message, "CQ zone 41 out of range 1-40");
+
+        // Strict parsing still stops at the first error, lenient parsing never fails
+        let err = CtyBuilder::new().parse(data).unwrap_err();
+        assert_eq!(err.downcast::<ParseError>().unwrap().line, 2);
+        let builder = CtyBuilder::new().all_errors(true).lenient(true);
+        assert_eq!(builder.parse(data).unwrap().1.len(), 4);
+        assert!(CtyBuilder::new().all_errors(true).load("cty.dat").is_ok());
+    }
+
+    #[test]
+    fn streaming_parser() {
+        let data = std::fs::read("cty.dat").unwrap();
+        let cty = Cty::new("cty.dat").unwrap();
+        let mut parser = Parser::new();
+        for chunk in data.chunks(7) {
+            parser.feed(chunk).unwrap();
+        }
+        let (streamed, warnings) = parser.finish().unwrap();
+        assert!(warnings.is_empty());
+        assert!(cty.diff(&streamed).is_empty());
+        assert_eq!(streamed.version(), cty.version());
+
+        // CRLF line endings, a last line without one and the line numbers of errors
+        let data = "Spain:  14:  37:  EU:   40.32:     3.43:    -1.0:  EA:\r\n    EA,EB(41);\r\n\
+                    Canary Islands:  33:  36:  AF:   28.32:    15.20:     0.0:  EA8:\r\n    EA8;";
+        let mut parser = CtyBuilder::new().lenient(true).parser();
+        for chunk in data.as_bytes().chunks(5) {
+            parser.feed(chunk).unwrap();
+        }
+        let (cty, warnings) = parser.finish().unwrap();
+        assert_eq!(warnings.iter().map(|w| w.line).collect::<Vec<_>>(), [2]);
+        assert_eq!(cty.lookup("EA8ABC").unwrap().name, "Canary Islands");
+        let mut strict = Parser::new();
+        assert_eq!(strict.feed(data.as_bytes()).unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn record_termination() {
+        let spain = "Spain:  14:  37:  EU:   40.32:     3.43:    -1.0:  EA:\n";
+        let canaries = "Canary Islands:  33:  36:  AF:   28.32:    15.20:     0.0:  EA8:\n";
+        let lenient = CtyBuilder::new().lenient(true);
+        let lines = |data: &str| {
+            let (_, warnings) = lenient.parse(data).unwrap();
+            warnings.iter().map(|w| w.line).collect::<Vec<_>>()
+        };
+
+        // Records span several alias lines
+        let data = format!("{spain}    EA,\n    EB,EC;\n{canaries}    EA8;\n");
+        let cty: Cty = data.parse().unwrap();
+        assert_eq!(cty.lookup("EC1ABC").unwrap().name, "Spain");
+
+        // Missing ; before the next header and at the end of the file
+        let data = format!("{spain}    EA,EB\n{canaries}    EA8\n");
+        assert_eq!(lines(&data), [3, 4]);
+        let err = data.parse::<Cty>().unwrap_err();
+        assert_eq!(err.downcast::<ParseError>().unwrap().line, 3);
+        assert_eq!(
+            lenient.parse(&data).unwrap().0.lookup("EA8A").unwrap().name,
+            "Canary Islands"
+        );
+
+        // Aliases after the end of a record
+        let data = format!("{spain}    EA,EB; EC\n    ED;\n{canaries}    EA8;\n");
+        assert_eq!(lines(&data), [2, 3]);
+        let (cty, _) = lenient.parse(&data).unwrap();
+        assert_eq!(cty.lookup("ED1ABC"), None);
+    }
+
+    #[test]
+    fn ranked_matches() {
+        let mut cty = Cty::new("cty.dat").unwrap();
+        let patterns = |cty: &Cty, call| {
+            cty.lookup_all(call)
+                .iter()
+                .map(|p| (p.pattern.clone(), p.resolved().name.clone()))
+                .collect::<Vec<_>>()
+        };
+        let matches = patterns(&cty, "KG4ABC");
+        assert_eq!(matches[0].0, "KG4");
+        assert_eq!(matches.last().unwrap().0, "K");
+        assert!(cty.lookup_all("KG4BIG")[0].is_exact);
+        assert!(cty.lookup_all("QQ1").is_empty());
+
+        let overlay = "Test Island:  40:  90:  OC:  1.0:  1.0:  0.0:  Q1:\n    DL1,DL;\n";
+        cty.merge(overlay.parse().unwrap());
+        assert_eq!(
+            patterns(&cty, "DL1ABC"),
+            [
+                ("DL1".to_string(), "Test Island".to_string()),
+                ("DL".to_string(), "Test Island".to_string()),
+                ("DL".to_string(), "Fed. Rep. of Germany".to_string()),
+            ]
+        );
+        assert_eq!(
+            cty.lookup_all("DL1ABC")[0].entity.name,
+            cty.lookup("DL1ABC").unwrap().name
+        );
+    }
+
+    #[test]
+    fn owned_lookups() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let entity = cty.lookup_owned("DL1ABC").unwrap();
+        // Shares the record with the database
+        assert!(core::ptr::eq(
+            entity.as_ref(),
+            cty.lookup("DL2ABC").unwrap()
+        ));
+        assert!(cty.lookup_owned("QQ1ABC").is_none());
+        drop(cty);
+
+        let name = std::thread::spawn(move || entity.name.clone())
+            .join()
+            .unwrap();
+        assert_eq!(name, "Fed. Rep. of Germany");
+    }
+
+    #[test]
+    fn static_lookups() {
+        static ENTITIES: [StaticEntity; 2] = [
+            StaticEntity {
+                name: "Fed. Rep. of Germany",
+                prefix: "DL",
+                dxcc: 230,
+                id: 230,
+                waedc: false,
+                deleted: false,
+            },
+            StaticEntity {
+                name: "Hawaii",
+                prefix: "KH6",
+                dxcc: 110,
+                id: 110,
+                waedc: false,
+                deleted: false,
+            },
+        ];
+        static PREFIXES: [StaticPrefix; 3] = [
+            StaticPrefix {
+                pattern: "DL",
+                exact: false,
+                entity: &ENTITIES[0],
+                cq: 14,
+                itu: 28,
+                continent: "EU",
+                lat: 51.0,
+                lon: -10.0,
+                utc_offset: 3600,
+            },
+            StaticPrefix {
+                pattern: "DL1XYZ",
+                exact: true,
+                entity: &ENTITIES[1],
+                cq: 31,
+                itu: 61,
+                continent: "OC",
+                lat: 21.0,
+                lon: 157.0,
+                utc_offset: -36000,
+            },
+            StaticPrefix {
+                pattern: "KH6",
+                exact: false,
+                entity: &ENTITIES[1],
+                cq: 31,
+                itu: 61,
+                continent: "OC",
+                lat: 21.0,
+                lon: 157.0,
+                utc_offset: -36000,
+            },
+        ];
+        static CTY: StaticCty = StaticCty {
+            version: None,
+            entities: &ENTITIES,
+            prefixes: &PREFIXES,
+            rules: &[("DL#HI", &PREFIXES[2])],
+        };
+        let name = |call| CTY.lookup(call).map(|p| p.entity.name);
+        assert_eq!(name("DL1ABC"), Some("Fed. Rep. of Germany"));
+        assert_eq!(name("DL1XYZ"), Some("Hawaii"));
+        assert_eq!(name("DL1ABC/KH6"), Some("Hawaii"));
+        assert_eq!(name("DL5HI"), Some("Hawaii"));
+        assert_eq!(name("QQ1ABC"), None);
+    }
+
+    #[cfg(feature = "codegen")]
+    #[test]
+    fn generated_source() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let source = codegen::generate(&cty, "CTY");
+        assert!(source.starts_with("// Generated by cty_rs::codegen from release 20231103"));
+        assert!(source.contains("static CTY_ENTITIES: [cty_rs::StaticEntity; 346] = ["));
+        assert!(source.contains("pub static CTY: cty_rs::StaticCty = cty_rs::StaticCty {"));
+        assert!(source.contains(
+            "cty_rs::StaticEntity { name: \"Fed. Rep. of Germany\", prefix: \"DL\", dxcc: 230,"
+        ));
+        // Prefixes are sorted for the binary search
+        let patterns = source
+            .lines()
+            .filter_map(|line| line.split("pattern: \"").nth(1))
+            .map(|rest| rest.split('"').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(patterns.len(), cty.prefixes().count());
+        assert!(patterns.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[cfg(feature = "localization")]
+    #[test]
+    fn localized_names() {
+        let mut cty = Cty::new("cty.dat").unwrap();
+        let ru = localization::Translations::parse("ru", r#"{"DL": "Германия", "ja": "Япония"}"#);
+        cty.apply_translations(&ru.unwrap());
+        let ja = localization::Translations::parse("ja", r#"{"DL": "ドイツ"}"#).unwrap();
+        cty.apply_translations(&ja);
+        let germany = cty.lookup("DL1ABC").unwrap();
+        assert_eq!(germany.name_localized("ru"), "Германия");
+        assert_eq!(germany.name_localized("ja-JP"), "ドイツ");
+        assert_eq!(germany.name_localized("fr"), "Fed. Rep. of Germany");
+        assert_eq!(cty.lookup("JA1ABC").unwrap().name_localized("ru"), "Япония");
+        assert_eq!(
+            cty.lookup("ON4ABC").unwrap().name_localized("ru"),
+            "Belgium"
+        );
+
+        let ru = localization::Translations::parse("ru", r#"{"JA": "Япония"}"#).unwrap();
+        cty.apply_translations(&ru);
+        assert_eq!(
+            cty.lookup("DL1ABC").unwrap().name_localized("ru"),
+            "Fed. Rep. of Germany"
+        );
+        assert_eq!(cty.lookup("DL1ABC").unwrap().name_localized("ja"), "ドイツ");
+        assert!(localization::Translations::parse("ru", "[]").is_err());
+    }
+
+    #[test]
+    fn runtime_aliases() {
+        let mut cty = Cty::new("cty.dat").unwrap();
+        let germany = EntityId(230);
+        let overrides = Overrides {
+            cq: Some(cq(15)),
+            ..Overrides::default()
+        };
+        cty.add_alias("=W1AW/DL", germany, overrides.clone())
+            .unwrap();
+        let entity = cty.lookup("W1AW/DL").unwrap();
+        assert_eq!(
+            (entity.name.as_str(), entity.cq),
+            ("Fed. Rep. of Germany", cq(15))
+        );
+        assert!(cty.entities_in_cq_zone(cq(15)).any(|e| e.prefix == "DL"));
+        assert!(cty
+            .add_alias("Q1", EntityId(9999), Overrides::default())
+            .is_err());
+
+        let entity = Entity {
+            name: "Test Island".to_string(),
+            prefix: "Q1".to_string(),
+            cq: cq(40),
+            itu: itu(90),
+            continent: Continent::OC,
+            ..Entity::default()
+        };
+        let id = cty.insert_entity(entity);
+        assert_eq!(cty.lookup("Q1ABC").unwrap().name, "Test Island");
+        cty.add_alias("q1", id, Overrides::default()).unwrap();
+        // Replaces the DL prefix, which comes back once the alias is removed
+        cty.add_alias("DL", id, Overrides::default()).unwrap();
+        assert_eq!(cty.lookup("Q1ABC").unwrap().name, "Test Island");
+        assert_eq!(cty.lookup("DL1ABC").unwrap().name, "Test Island");
+        assert_eq!(cty.remove_alias("DL").unwrap().entity.name, "Test Island");
+        assert_eq!(cty.lookup("DL1ABC").unwrap().name, "Fed. Rep. of Germany");
+        // Also when only the overrides change
+        cty.add_alias("DL", germany, overrides).unwrap();
+        assert_eq!(cty.lookup("DL1ABC").unwrap().cq, cq(15));
+        cty.remove_alias("DL").unwrap();
+        assert_eq!(cty.lookup("DL1ABC").unwrap().cq, cq(14));
+        // Changing a pattern again keeps only the definition from the file
+        let matches = cty.lookup_all("DL1ABC").len();
+        for _ in 0..3 {
+            cty.add_alias("DL", id, Overrides::default()).unwrap();
+            cty.add_alias("DL", germany, Overrides::default()).unwrap();
+        }
+        cty.add_alias("DL", id, Overrides::default()).unwrap();
+        assert_eq!(cty.lookup_all("DL1ABC").len(), matches + 1);
+        let conflicts = |cty: &Cty| {
+            let issues = cty.validate().into_iter();
+            issues
+                .filter_map(|issue| match issue {
+                    Issue::ConflictingPrefix { pattern, entities } if pattern == "DL" => {
+                        Some(entities)
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            conflicts(&cty),
+            [vec!["Fed. Rep. of Germany", "Test Island"]]
+        );
+        cty.remove_alias("DL").unwrap();
+        assert_eq!(cty.lookup("DL1ABC").unwrap().cq, cq(14));
+        assert_eq!(cty.lookup_all("DL1ABC").len(), matches);
+        assert!(conflicts(&cty).is_empty());
+
+        // Exact calls are only removed as exact calls
+        assert!(cty.remove_alias("W1AW/DL").is_none());
+        assert!(cty.remove_alias("=W1AW/DL").is_some());
+        assert_eq!(cty.lookup("W1AW/DL").unwrap().cq, cq(14));
+
+        let mut entity = cty.entities().find(|e| e.prefix == "Q1").unwrap().clone();
+        entity.name = "Renamed Island".to_string();
+        assert_eq!(cty.insert_entity(entity), id);
+        assert_eq!(cty.entities().count(), 347);
+        assert_eq!(cty.lookup("Q1ABC").unwrap().name, "Renamed Island");
+    }
+
+    #[test]
+    fn exported_overrides() {
+        let mut cty = Cty::new("cty.dat").unwrap();
+        assert_eq!(cty.overrides_to_string(), "");
+        let overrides = Overrides {
+            cq: Some(cq(15)),
+            lat: Some(50.5),
+            timezone: offset_east(-19800),
+            ..Overrides::default()
+        };
+        cty.add_alias("=DL0XYZ", EntityId(230), overrides).unwrap();
+        let entity = Entity {
+            name: "Test Island".to_string(),
+            prefix: "Q1".to_string(),
+            cq: cq(40),
+            itu: itu(90),
+            continent: Continent::OC,
+            lat: 1.25,
+            lon: -2.5,
+            timezone: offset_east(20700).unwrap(),
+            ..Entity::default()
+        };
+        cty.insert_entity(entity);
+        cty.add_alias("KH6", EntityId(110), Overrides::default())
+            .unwrap();
+        cty.add_alias("Q2", EntityId(110), Overrides::default())
+            .unwrap();
+        cty.remove_alias("Q2");
+        // Left out, reading it back would bring back its primary prefix
+        cty.insert_entity(Entity {
+            name: "Bare Island".to_string(),
+            prefix: "Q3".to_string(),
+            ..Entity::default()
+        });
+        cty.remove_alias("Q3").unwrap();
+        let overlay = cty.overrides_to_string();
+        assert_eq!(
+            overlay,
+            "Fed. Rep. of Germany:  14:  28:  EU:  51:  -10:  -1:  DL:\n    =DL0XYZ(15)<50.5/-10>~5.5~;\n\
+             Hawaii:  31:  61:  OC:  21.12:  157.48:  10:  KH6:\n    KH6;\n\
+             Test Island:  40:  90:  OC:  1.25:  -2.5:  -5.75:  Q1:\n    Q1;\n"
+        );
+
+        // The changes carry over to a fresh copy of the file
+        let path = std::env::temp_dir().join("cty-rs-exported-overrides.dat");
+        cty.export_overrides(&path).unwrap();
+        let (reloaded, _) = CtyBuilder::new()
+            .with_file(path.to_str().unwrap())
+            .load("cty.dat")
+            .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let entity = reloaded.lookup("DL0XYZ").unwrap();
+        assert_eq!(
+            (entity.cq, entity.lat, entity.utc_offset()),
+            (cq(15), 50.5, -19800)
+        );
+        assert_eq!(reloaded.lookup("DL1ABC").unwrap().cq, cq(14));
+        assert!(reloaded.entities().any(|e| e.name == "Test Island"));
+        assert_eq!(reloaded.lookup("Q2ABC"), None);
+        let matches = |cty: &Cty, call| {
+            let matches = cty.lookup_all(call).into_iter();
+            matches
+                .map(|p| {
+                    let e = p.resolved();
+                    let place = (e.cq, e.itu, e.lat, e.lon, e.utc_offset());
+                    (p.pattern.clone(), p.is_exact, e.name.clone(), place)
+                })
+                .collect::<Vec<_>>()
+        };
+        for call in ["DL0XYZ", "DL1ABC", "KH6ABC", "Q1ABC", "Q2ABC", "Q3ABC"] {
+            assert_eq!(matches(&reloaded, call), matches(&cty, call), "{call}");
+        }
+    }
+
+    #[test]
+    fn entities_by_id() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let stored = u16::from(cty.lookup("IT9ABC").unwrap().id());
+        assert_eq!(stored, 1004);
+        let reloaded = Cty::new("cty.dat").unwrap();
+        let sicily = reloaded.entity_by_id(EntityId::from(stored)).unwrap();
+        assert_eq!(sicily.name, "Sicily");
+        assert_eq!(reloaded.entity_by_id(EntityId(230)).unwrap().prefix, "DL");
+        assert!(!sicily.id().is_positional());
+        assert_eq!(reloaded.entity_by_id(EntityId(9999)), None);
+        assert_eq!(sicily.id().to_string(), "1004");
+        // Every id maps back to its own entity
+        for entity in reloaded.entities() {
+            assert_eq!(reloaded.entity_by_id(entity.id()), Some(entity));
+        }
+    }
+
+    #[test]
+    fn entities_by_dxcc() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let germany = cty.entity_by_dxcc(230).unwrap();
+        assert_eq!((germany.prefix.as_str(), germany.cq), ("DL", cq(14)));
+        // Italy, not Sicily which shares its code
+        assert_eq!(cty.entity_by_dxcc(248).unwrap().prefix, "I");
+        assert_eq!(cty.entity_by_dxcc(110).unwrap().name, "Hawaii");
+        assert_eq!(cty.entity_by_dxcc(0), None);
+        // Deleted entities aren't in cty.dat
+        assert_eq!(cty.entity_by_dxcc(2), None);
+    }
+
+    #[test]
+    fn adif_country_names() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let name = |call| cty.lookup(call).unwrap().adif_country_name();
+        assert_eq!(name("DL1ABC"), Some("FEDERAL REPUBLIC OF GERMANY"));
+        assert_eq!(name("W1AW"), Some("UNITED STATES OF AMERICA"));
+        assert_eq!(name("IT9ABC"), Some("ITALY"));
+        assert_eq!(name("GM/DL1ABC"), Some("SCOTLAND"));
+        assert_eq!(adif_country_name(0), None);
+        // Every current DXCC entity has its ADIF name
+        assert!(cty.entities().all(|e| e.adif_country_name().is_some()));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn geojson_export() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let geojson: serde_json::Value = serde_json::from_str(&cty.to_geojson()).unwrap();
+        assert_eq!(geojson["type"], "FeatureCollection");
+        let features = geojson["features"].as_array().unwrap();
+        assert_eq!(features.len(), 346);
+        let germany = features
+            .iter()
+            .find(|f| f["properties"]["prefix"] == "DL")
+            .unwrap();
+        assert_eq!(germany["type"], "Feature");
+        assert_eq!(germany["geometry"]["type"], "Point");
+        // East longitude first
+        assert_eq!(
+            germany["geometry"]["coordinates"],
+            serde_json::json!([10.0, 51.0])
+        );
+        let properties = &germany["properties"];
+        assert_eq!(properties["name"], "Fed. Rep. of Germany");
+        assert_eq!(
+            (properties["cq"].as_u64(), properties["itu"].as_u64()),
+            (Some(14), Some(28))
+        );
+        assert_eq!(properties["continent"], "EU");
+        assert_eq!(properties["dxcc"], 230);
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn arrow_batches() {
+        use arrow_array::{Array, StringArray, UInt8Array};
+
+        let cty = Cty::new("cty.dat").unwrap();
+        let column = |batch: &arrow_array::RecordBatch, name| batch.column_by_name(name).cloned();
+        let entities = cty.entities_batch().unwrap();
+        assert_eq!(entities.num_rows(), cty.entities().count());
+        let prefixes = cty.prefixes_batch().unwrap();
+        assert_eq!(prefixes.num_rows(), cty.prefixes().count());
+        let patterns = column(&prefixes, "pattern").unwrap();
+        let patterns = patterns.as_any().downcast_ref::<StringArray>().unwrap();
+        // Rows carry their overrides, =AA0Y(3)[6] is in CQ zone 3
+        let row = patterns.iter().position(|p| p == Some("AA0Y")).unwrap();
+        let entity = column(&prefixes, "prefix").unwrap();
+        let entity = entity.as_any().downcast_ref::<StringArray>().unwrap();
+        let cq = column(&prefixes, "cq").unwrap();
+        let cq = cq.as_any().downcast_ref::<UInt8Array>().unwrap();
+        assert_eq!((entity.value(row), cq.value(row)), ("K", 3));
+
+        let lookups = cty
+            .lookups_batch(["DL1ABC", "W1ABC/KH6", "QQ1ABC"])
+            .unwrap();
+        let names = column(&lookups, "name").unwrap();
+        let names = names.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(names.value(0), "Fed. Rep. of Germany");
+        assert_eq!(names.value(1), "Hawaii");
+        assert!(names.is_null(2));
+        let cq = column(&lookups, "cq").unwrap();
+        let cq = cq.as_any().downcast_ref::<UInt8Array>().unwrap();
+        assert_eq!((cq.value(0), cq.value(1)), (14, 31));
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn parquet_export() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let cty = Cty::new("cty.dat").unwrap();
+        let entities = cty.entities_batch().unwrap();
+        let path = std::env::temp_dir().join("cty-rs-entities.parquet");
+        columnar::write_parquet(&entities, std::fs::File::create(&path).unwrap()).unwrap();
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(batches.iter().map(|b| b.num_rows()).sum::<usize>(), 346);
+        assert_eq!(batches[0].schema(), entities.schema());
+    }
+
+    #[test]
+    fn nearest_entities() {
+        let cty = Cty::new("cty.dat").unwrap();
+        // Frankfurt
+        let nearest = cty.nearest_entities(50.1, 8.7, 5);
+        assert_eq!(nearest.len(), 5);
+        assert_eq!(nearest[0].0.prefix, "DL");
+        assert_eq!(nearest[0].0, cty.nearest_entity(50.1, 8.7).unwrap());
+        assert!(nearest.windows(2).all(|w| w[0].1 <= w[1].1));
+        assert!(nearest.iter().any(|(e, _)| e.prefix == "LX"));
+        // DL's coordinates are 51N 10E
+        assert!((130.0..140.0).contains(&nearest[0].1));
+
+        assert_eq!(cty.nearest_entities(50.1, 8.7, 0).len(), 0);
+        assert_eq!(cty.nearest_entities(50.1, 8.7, 1000).len(), 346);
+        assert!(Cty::default().nearest_entities(0.0, 0.0, 3).is_empty());
+    }
+
+    #[test]
+    fn heading_table() {
+        let cty = Cty::new("cty.dat").unwrap();
+        // From Frankfurt
+        let table = cty.heading_table(50.1, 8.7);
+        assert_eq!(table.len(), 346);
+        assert!(table.iter().map(|(e, _)| *e).eq(cty.entities()));
+        let heading = |prefix| table.iter().find(|(e, _)| e.prefix == prefix).unwrap().1;
+        let japan = heading("JA");
+        assert!((30.0..45.0).contains(&japan.bearing), "{}", japan.bearing);
+        assert!((japan.long_bearing - japan.bearing - 180.0).abs() < 1e-9);
+        let (lat, lon) = cty.lookup("VK2ABC").unwrap().coordinates();
+        assert_eq!(heading("VK"), geo::path(50.1, 8.7, lat, lon));
+    }
+
+    #[cfg(feature = "timezone")]
+    #[test]
+    fn gray_line_overlap() {
+        let cty = Cty::new("cty.dat").unwrap();
+        let date = chrono::NaiveDate::from_ymd_opt(2023, 12, 21).unwrap();
+        let margin = chrono::Duration::minutes(60);
+        // Sunrise in Germany meets sunset in Japan
+        let overlaps = cty.gray_line("DL1ABC", "JA1ABC", date, margin).unwrap();
+        assert_eq!(overlaps.len(), 1);
+        let overlap = overlaps[0];
+        assert!(overlap.from_sunrise && !overlap.to_sunrise);
+        let (lat, lon) = cty.lookup("DL1ABC").unwrap().coordinates();
+        let (sunrise, _) = sun::crossings(lat, lon, date, sun::SUNRISE_ALTITUDE);
+        assert_eq!(overlap.end, sunrise.unwrap() + margin);
+        let (lat, lon) = cty.lookup("JA1ABC").unwrap().coordinates();
+        let (_, sunset) = sun::crossings(lat, lon, date, sun::SUNRISE_ALTITUDE);
+        assert_eq!(overlap.start, sunset.unwrap() - margin);
+
+        // A narrower margin closes the window
+        let narrow = chrono::Duration::minutes(5);
+        assert!(cty
+            .gray_line("DL1ABC", "JA1ABC", date, narrow)
+            .unwrap()
+            .is_empty());
+        assert!(cty.gray_line("DL1ABC", "QQ1ABC", date, margin).is_none());
+    }
+
+    #[cfg(feature = "compressed")]
+    #[test]
+    fn compressed_files() {
+        use flate2::{write::DeflateEncoder, write::GzEncoder, Compression, Crc};
+        use std::io::{Read, Write};
+
+        let data = std::fs::read("cty.dat").unwrap();
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(&data).unwrap();
+        let gz = gz.finish().unwrap();
+
+        // A zip archive with a stored cty.csv ahead of the deflated cty.dat
+        let mut zip = Vec::new();
+        let mut directory = Vec::new();
+        let mut deflate = DeflateEncoder::new(Vec::new(), Compression::default());
+        deflate.write_all(&data).unwrap();
+        let entries = [
+            (
+                "cty.csv",
+                0u16,
+                b"1A,Malta\n".to_vec(),
+                b"1A,Malta\n".to_vec(),
+            ),
+            ("bundle/CTY.DAT", 8, data.clone(), deflate.finish().unwrap()),
+        ];
+        for (name, method, contents, stored) in &entries {
+            let mut crc = Crc::new();
+            crc.update(contents);
+            let offset = zip.len() as u32;
+            // Flags, method, time and date, CRC, sizes, name and extra lengths
+            let mut fields = vec![0, 0];
+            fields.extend_from_slice(&method.to_le_bytes());
+            fields.extend_from_slice(&[0; 4]);
+            fields.extend_from_slice(&crc.sum().to_le_bytes());
+            fields.extend_from_slice(&(stored.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            fields.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            fields.extend_from_slice(&[0; 2]);
+            zip.extend_from_slice(b"PK\x03\x04\x14\x00");
+            zip.extend_from_slice(&fields);
+            zip.extend_from_slice(name.as_bytes());
+            zip.extend_from_slice(stored);
+            directory.extend_from_slice(b"PK\x01\x02\x14\x00\x14\x00");
+            directory.extend_from_slice(&fields);
+            // Comment length, disk, attributes
+            directory.extend_from_slice(&[0; 10]);
+            directory.extend_from_slice(&offset.to_le_bytes());
+            directory.extend_from_slice(name.as_bytes());
+        }
+        let directory_offset = zip.len() as u32;
+        zip.extend_from_slice(&directory);
+        zip.extend_from_slice(b"PK\x05\x06\0\0\0\0\x02\0\x02\0");
+        zip.extend_from_slice(&(directory.len() as u32).to_le_bytes());
+        zip.extend_from_slice(&directory_offset.to_le_bytes());
+        zip.extend_from_slice(&[0; 2]);
+
+        let dir = std::env::temp_dir();
+        for (name, contents) in [("cty-rs-test.dat.gz", &gz), ("cty-rs-test.zip", &zip)] {
+            let path = dir.join(name);
+            std::fs::write(&path, contents).unwrap();
+            let cty = Cty::new(path.to_str().unwrap());
+            std::fs::remove_file(&path).unwrap();
+            let cty = cty.unwrap();
+            assert_eq!(cty.entities().count(), 346, "{name}");
+            assert_eq!(cty.lookup("DL1ABC").unwrap().name, "Fed. Rep. of Germany");
+        }
+
+        // A corrupted entry fails its checksum
+        let corrupted = zip.len() - directory.len() - 22 - 1;
+        zip[corrupted] ^= 0xff;
+        let path = dir.join("cty-rs-corrupted.zip");
+        std::fs::write(&path, &zip).unwrap();
+        let cty = Cty::new(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert!(cty.is_err());
+        zip[corrupted] ^= 0xff;
+
+        // Files expanding to more than the limit fail, whichever the format
+        let limit = data.len() as u64;
+        for (name, contents) in [("gzip", &gz), ("zip", &zip)] {
+            let decompressed = compressed::decompress(contents.clone(), limit).unwrap();
+            assert_eq!(decompressed, data, "{name}");
+            let decompressed = compressed::decompress(contents.clone(), limit - 1);
+            assert!(decompressed.is_err(), "{name}");
+        }
+        // Streamed gzip stops a byte past the limit, for downloads to notice
+        let mut read = Vec::new();
+        let mut reader = compressed::reader(&gz[..], 1000).unwrap();
+        reader.read_to_end(&mut read).unwrap();
+        assert_eq!(read.len(), 1001);
+        assert!(compressed::reader(&zip[..], 1000).is_err());
+    }
+
+    #[cfg(feature = "watch")]
+    #[test]
+    fn watched_reload() {
+        use std::time::Duration;
+
+        let path = std::env::temp_dir().join("cty-rs-watch.dat");
+        let header = "Singapore:  28:  54:  AS:  1.37:  -103.78:  -8.0:  9V:\n";
+        std::fs::write(&path, format!("{header}    9V;\n")).unwrap();
+        let watched = WatchedCty::new(path.to_str().unwrap(), Duration::from_millis(10)).unwrap();
+        assert!(watched.lookup("S6ABC").is_none());
+
+        // Make sure the modification time changes on coarse filesystems
+        std::thread::sleep(Duration::from_millis(1100));
+        std::fs::write(&path, format!("{header}    9V,S6;\n")).unwrap();
+        let reloaded = (0..300).any(|_| {
+            std::thread::sleep(Duration::from_millis(10));
+            watched.lookup("S6ABC").is_some()
+        });
+        assert!(reloaded);
+        assert_eq!(watched.current().entities().count(), 1);
+    }
+
+    #[test]
+    fn parse_from_str() {
+        let cty: Cty = include_str!("../cty.dat").parse().unwrap();
+        assert_eq!(cty.entities().count(), 346);
+        assert_eq!(cty.lookup("9V1AAA").unwrap().name, "Singapore");
+        assert_eq!(
+            lint::lint_str(
+                "Singapore:  28:  54:  AS:  1.37:  -103.78:  -8.0:  9V:\n    9V<91.0/0.0>;\n"
+            )
+            .unwrap()
+            .len(),
+            1
+        );
+    }
+
+    #[cfg(feature = "ffi")]
+    #[test]
+    fn ffi_lookup() {
+        use cty_ffi::*;
+        use std::ffi::{CStr, CString};
+
+        unsafe {
+            let db = cty_load(CString::new("cty.dat").unwrap().as_ptr());
+            assert!(!db.is_null());
+            let mut entity = std::mem::zeroed::<CtyEntity>();
+            let call = CString::new("9V1AAA").unwrap();
+            assert!(cty_lookup(db, call.as_ptr(), &mut entity));
+            assert_eq!(CStr::from_ptr(entity.name).to_str().unwrap(), "Singapore");
+            assert_eq!(entity.utc_offset, 8 * 3600);
+            assert!(!cty_lookup(
+                db,
+                CString::new("").unwrap().as_ptr(),
+                &mut entity
+            ));
+            cty_free(db);
+            assert!(cty_load(CString::new("missing.dat").unwrap().as_ptr()).is_null());
+        }
+    }
 }
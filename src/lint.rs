@@ -0,0 +1,85 @@
+// Sanity checks of a country file that report the offending line numbers
+use crate::{parser, Record};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::error::Error;
+use hashbrown::HashMap;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+fn check_coordinates(lat: Option<f32>, lon: Option<f32>) -> Vec<String> {
+    let mut messages = Vec::new();
+    if let Some(lat) = lat.filter(|lat| !(-90.0..=90.0).contains(lat)) {
+        messages.push(format!("Latitude {lat} out of range"));
+    }
+    if let Some(lon) = lon.filter(|lon| !(-180.0..=180.0).contains(lon)) {
+        messages.push(format!("Longitude {lon} out of range"));
+    }
+    messages
+}
+
+// Parse the file strictly, failing with a `ParseError` on the first malformed
+// record (out of range zones included), and report conflicting duplicates and
+// out of range coordinates
+#[cfg(feature = "fs")]
+pub fn lint_file(filename: &str) -> Result<Vec<Diagnostic>, Box<dyn Error>> {
+    lint_str(&crate::read_file(filename)?)
+}
+
+// Same as `lint_file` for the contents of a country file
+pub fn lint_str(data: &str) -> Result<Vec<Diagnostic>, Box<dyn Error>> {
+    lint_lines(crate::str_lines(data))
+}
+
+fn lint_lines<L: AsRef<str>, E: Into<Box<dyn Error>>>(
+    lines: impl Iterator<Item = Result<L, E>>,
+) -> Result<Vec<Diagnostic>, Box<dyn Error>> {
+    let mut diagnostics = Vec::new();
+    // Pattern to the entity and line it was first seen with
+    let mut seen: HashMap<String, (String, usize)> = HashMap::new();
+    let mut entity = String::new();
+    let mut waedc = false;
+
+    parser::parse_lines(lines, false, |line, record| {
+        let messages = match record {
+            Record::Entity(e) => {
+                entity = e.name.clone();
+                waedc = e.waedc;
+                check_coordinates(Some(e.lat), Some(e.lon))
+            }
+            Record::Prefix {
+                pattern, overrides, ..
+            } => {
+                let mut messages = check_coordinates(overrides.lat, overrides.lon);
+                match seen.get(pattern) {
+                    // WAE-only entities repeat calls of their DXCC entity on purpose
+                    Some(_) if waedc => {}
+                    Some((other, other_line)) if *other != entity => messages.push(format!(
+                        "{pattern} of {entity} is also defined for {other} on line {other_line}"
+                    )),
+                    Some(_) => {}
+                    None if !waedc => {
+                        seen.insert(pattern.to_string(), (entity.clone(), line));
+                    }
+                    None => {}
+                }
+                messages
+            }
+        };
+        diagnostics.extend(
+            messages
+                .into_iter()
+                .map(|message| Diagnostic { line, message }),
+        );
+        Ok(())
+    })?;
+    Ok(diagnostics)
+}
@@ -0,0 +1,58 @@
+// Entity names in other languages, loaded from JSON objects of primary
+// prefix to name, e.g. {"DL": "Германия", "JA": "Япония"} for Russian
+use crate::{Cty, Entity};
+use std::{collections::HashMap, error::Error};
+
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    // Language tag like "ru" or "pt-BR"
+    pub lang: String,
+    // Primary prefix and localized name
+    pub names: HashMap<String, String>,
+}
+
+impl Translations {
+    pub fn parse(lang: &str, data: &str) -> Result<Translations, Box<dyn Error>> {
+        let names: HashMap<String, String> = serde_json::from_str(data)?;
+        Ok(Translations {
+            lang: lang.to_string(),
+            names: names
+                .into_iter()
+                .map(|(prefix, name)| (prefix.trim().to_ascii_uppercase(), name))
+                .collect(),
+        })
+    }
+}
+
+impl Entity {
+    // The name in the given language, falling back from "pt-BR" to "pt" and
+    // then to the name in the country file
+    pub fn name_localized(&self, lang: &str) -> &str {
+        let base = lang.split_once('-').map(|(base, _)| base);
+        [Some(lang), base]
+            .into_iter()
+            .flatten()
+            .find_map(|lang| self.localized_names.get(lang))
+            .unwrap_or(&self.name)
+    }
+}
+
+impl Cty {
+    #[cfg(feature = "fs")]
+    pub fn load_translations(&mut self, lang: &str, filename: &str) -> Result<(), Box<dyn Error>> {
+        let translations = Translations::parse(lang, &std::fs::read_to_string(filename)?)?;
+        self.apply_translations(&translations);
+        Ok(())
+    }
+    // Add the names of a language, replacing ones loaded for it before.
+    // Entities missing from the table keep their English name.
+    pub fn apply_translations(&mut self, translations: &Translations) {
+        let lang = &translations.lang;
+        self.update_entities(|entity| {
+            match translations.names.get(&entity.prefix.to_ascii_uppercase()) {
+                Some(name) => entity.localized_names.insert(lang.clone(), name.clone()),
+                None => entity.localized_names.remove(lang),
+            };
+        });
+    }
+}
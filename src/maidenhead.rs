@@ -0,0 +1,68 @@
+// Conversions between coordinates and Maidenhead locators.
+// Coordinates are in degrees with north latitude and east longitude positive.
+use std::error::Error;
+
+// Size in degrees of longitude and latitude of each locator pair and its base
+const PAIRS: [(f64, f64, u8); 4] = [
+    (20.0, 10.0, b'A'),
+    (2.0, 1.0, b'0'),
+    (2.0 / 24.0, 1.0 / 24.0, b'a'),
+    (2.0 / 240.0, 1.0 / 240.0, b'0'),
+];
+
+// Encode a position as a 2, 4, 6 or 8 character locator
+pub fn to_locator(lat: f64, lon: f64, len: usize) -> Result<String, Box<dyn Error>> {
+    if !(2..=8).contains(&len) || !len.is_multiple_of(2) {
+        return Err("Locator length must be 2, 4, 6 or 8".into());
+    }
+    if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lon) {
+        return Err("Coordinates out of range".into());
+    }
+    // Keep the poles and the antimeridian inside the last field
+    let mut lon = (lon + 180.0).min(360.0 - 1e-9);
+    let mut lat = (lat + 90.0).min(180.0 - 1e-9);
+    let mut locator = String::with_capacity(len);
+    for &(lon_size, lat_size, base) in &PAIRS[..len / 2] {
+        let x = (lon / lon_size).floor();
+        let y = (lat / lat_size).floor();
+        locator.push((base + x as u8) as char);
+        locator.push((base + y as u8) as char);
+        lon -= x * lon_size;
+        lat -= y * lat_size;
+    }
+    Ok(locator)
+}
+
+// Decode a locator into the position of the center of its square
+pub fn from_locator(locator: &str) -> Result<(f64, f64), Box<dyn Error>> {
+    let bytes = locator.as_bytes();
+    if !(2..=8).contains(&bytes.len()) || !bytes.len().is_multiple_of(2) {
+        return Err("Locator length must be 2, 4, 6 or 8".into());
+    }
+    let (mut lat, mut lon) = (-90.0, -180.0);
+    let (mut lat_size, mut lon_size) = (0.0, 0.0);
+    for (pair, &(pair_lon_size, pair_lat_size, base)) in bytes.chunks(2).zip(&PAIRS) {
+        let limit = match base {
+            b'A' => 18,
+            b'a' => 24,
+            _ => 10,
+        };
+        // Letter pairs take either case, number pairs only digits
+        let digit = |c: u8| {
+            let value = match base {
+                b'0' if c.is_ascii_digit() => c.checked_sub(b'0'),
+                b'A' | b'a' if c.is_ascii_alphabetic() => c.to_ascii_uppercase().checked_sub(b'A'),
+                _ => None,
+            };
+            value
+                .filter(|&v| v < limit)
+                .map(f64::from)
+                .ok_or_else(|| format!("Invalid locator {locator}"))
+        };
+        lon += digit(pair[0])? * pair_lon_size;
+        lat += digit(pair[1])? * pair_lat_size;
+        lon_size = pair_lon_size;
+        lat_size = pair_lat_size;
+    }
+    Ok((lat + lat_size / 2.0, lon + lon_size / 2.0))
+}
@@ -0,0 +1,42 @@
+// Parsing country files straight from a memory map
+use crate::{Cty, CtyBuilder, ParseWarning};
+use memmap2::Mmap;
+use std::{error::Error, fs::File};
+
+// A country file mapped into memory, its text can be borrowed for as long
+// as the map lives
+#[derive(Debug)]
+pub struct MappedFile {
+    map: Mmap,
+}
+
+impl MappedFile {
+    pub fn open(filename: &str) -> Result<MappedFile, Box<dyn Error>> {
+        let file = File::open(filename)?;
+        // Safety: the map is read only, the file must not be truncated or
+        // rewritten in place while it is mapped. Updates that replace the
+        // file (write and rename) leave the mapped copy alone.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(MappedFile { map })
+    }
+    pub fn as_str(&self) -> Result<&str, Box<dyn Error>> {
+        Ok(core::str::from_utf8(&self.map)?)
+    }
+}
+
+impl CtyBuilder {
+    // Like `load`, with every file memory-mapped instead of read line by line
+    pub fn load_mmap(&self, filename: &str) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        let parse_file = |filename: &str| {
+            let file = MappedFile::open(filename)?;
+            self.parse_bytes(&file.map)
+        };
+        self.layer_with(parse_file(filename)?, parse_file)
+    }
+}
+
+impl Cty {
+    pub fn load_mmap(filename: &str) -> Result<Cty, Box<dyn Error>> {
+        CtyBuilder::new().load_mmap(filename).map(|(cty, _)| cty)
+    }
+}
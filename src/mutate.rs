@@ -0,0 +1,186 @@
+// Applying corrections to a loaded database, e.g. a special-event call
+// announced during a contest, without reloading the country files
+use crate::{dxcc, table::ResolvedCache, Cty, Entity, EntityId, Overrides, Prefix};
+use alloc::{boxed::Box, format, string::String, sync::Arc, vec::Vec};
+use core::{error::Error, fmt::Write};
+
+// Hours west of UTC as cty.dat gives them, e.g. -5.5 for India
+fn hours_west(seconds_east: i32) -> f32 {
+    (-seconds_east) as f32 / 3600.0
+}
+
+// An alias with its override tokens, e.g. "=DL1ABC(15)<51.0/-10.0>"
+fn alias_line(prefix: &Prefix) -> String {
+    let (overrides, resolved) = (&prefix.overrides, prefix.resolved());
+    let mut line = String::new();
+    if prefix.is_exact {
+        line.push('=');
+    }
+    line.push_str(&prefix.pattern);
+    // Writing to a String can't fail
+    if let Some(cq) = overrides.cq {
+        let _ = write!(line, "({cq})");
+    }
+    if let Some(itu) = overrides.itu {
+        let _ = write!(line, "[{itu}]");
+    }
+    if overrides.lat.is_some() || overrides.lon.is_some() {
+        let _ = write!(line, "<{}/{}>", resolved.lat, resolved.lon);
+    }
+    if let Some(continent) = &overrides.continent {
+        let _ = write!(line, "{{{continent}}}");
+    }
+    if overrides.timezone.is_some() {
+        let _ = write!(line, "~{}~", hours_west(resolved.utc_offset()));
+    }
+    line
+}
+
+// Pattern and exactness of an alias in country file syntax, "=CALL" for an
+// exact call
+fn alias_pattern(alias: &str) -> (String, bool) {
+    let alias = alias.trim();
+    match alias.strip_prefix('=') {
+        Some(call) => (call.to_ascii_uppercase(), true),
+        None => (alias.to_ascii_uppercase(), false),
+    }
+}
+
+impl Cty {
+    // Add an entity, or replace the one with the same primary prefix and
+    // keep its prefixes pointing at the new record. Like an entity read from
+    // a file a new one gets its primary prefix, add others with `add_alias`.
+    pub fn insert_entity(&mut self, mut entity: Entity) -> EntityId {
+        let index = self.entities.iter().position(|e| e.prefix == entity.prefix);
+        let i = index.unwrap_or(self.entities.len());
+        entity.id = dxcc::entity_id(&entity.prefix, entity.waedc, entity.dxcc, i);
+        let id = entity.id;
+        self.edited_entities.insert(entity.prefix.clone());
+        match index {
+            Some(i) => {
+                self.entities[i] = Arc::new(entity);
+                self.relink_prefixes(&mut ResolvedCache::default());
+            }
+            None => {
+                self.entities.push(Arc::new(entity));
+                let entity = &self.entities[i];
+                let prefix = Prefix::new(&entity.prefix, false, Overrides::default(), entity, i);
+                let previous = self.prefixes.insert(prefix);
+                self.edited_aliases
+                    .entry(entity.prefix.clone())
+                    .or_insert(previous);
+            }
+        }
+        self.build_indexes();
+        id
+    }
+    // Map a prefix, or an exact call written as "=CALL", to an entity,
+    // replacing any alias with the same pattern until `remove_alias`
+    pub fn add_alias(
+        &mut self,
+        alias: &str,
+        entity: EntityId,
+        overrides: Overrides,
+    ) -> Result<(), Box<dyn Error>> {
+        let (pattern, is_exact) = alias_pattern(alias);
+        if pattern.is_empty() {
+            return Err("Empty alias".into());
+        }
+        let index = self
+            .entities
+            .iter()
+            .position(|e| e.id == entity)
+            .ok_or_else(|| format!("Unknown entity {entity} for {pattern}"))?;
+        let prefix = Prefix::new(&pattern, is_exact, overrides, &self.entities[index], index);
+        // Only the definition from before the first change is kept
+        let previous = self.prefixes.insert(prefix);
+        self.edited_aliases.entry(pattern).or_insert(previous);
+        self.build_indexes();
+        Ok(())
+    }
+    // Remove a prefix or "=CALL" exact call, bringing back the definition it
+    // replaced if there was one: the one from before `add_alias` changed the
+    // pattern, or else the one a merged file replaced. None if the database
+    // has no such alias.
+    pub fn remove_alias(&mut self, alias: &str) -> Option<Prefix> {
+        let (pattern, is_exact) = alias_pattern(alias);
+        if self.prefixes.get(&pattern)?.is_exact != is_exact {
+            return None;
+        }
+        let removed = self.prefixes.remove(&pattern)?;
+        let previous = match self.edited_aliases.remove(&pattern) {
+            Some(previous) => previous,
+            None => {
+                let i = self.shadowed.iter().rposition(|p| p.pattern == pattern);
+                i.map(|i| self.shadowed.remove(i))
+            }
+        };
+        if let Some(previous) = previous {
+            self.prefixes.insert(previous);
+        }
+        self.build_indexes();
+        Some(removed)
+    }
+    // The definition `add_alias` replaced, if it was for another entity or
+    // exactness than the current one
+    pub(crate) fn replaced_by_edit(&self, pattern: &str) -> Option<&Prefix> {
+        let current = self.prefixes.get(pattern)?;
+        let previous = self.edited_aliases.get(pattern)?.as_ref()?;
+        (previous.entity_index != current.entity_index || previous.is_exact != current.is_exact)
+            .then_some(previous)
+    }
+}
+
+impl Cty {
+    // The entities and aliases changed with the functions above, as a
+    // country file to layer on a later release with `CtyBuilder::with_file`
+    // or `merge`. The entity of an added alias is written with its current
+    // record, which replaces the one of that release. Removed aliases that
+    // came from a file can't be expressed and are left out, and so are
+    // entities whose primary prefix was removed, as reading their record
+    // back would add it again.
+    pub fn overrides_to_string(&self) -> String {
+        let mut out = String::new();
+        for (index, entity) in self.entities.iter().enumerate() {
+            let primary = self.prefixes.get(&entity.prefix);
+            if !primary.is_some_and(|p| p.entity_index == index && !p.is_exact) {
+                continue;
+            }
+            let mut aliases = self
+                .edited_aliases
+                .keys()
+                .filter_map(|pattern| self.prefixes.get(pattern))
+                .filter(|p| p.entity_index == index)
+                .map(alias_line)
+                .peekable();
+            if aliases.peek().is_none() && !self.edited_entities.contains(&entity.prefix) {
+                continue;
+            }
+            let _ = writeln!(
+                out,
+                "{}:  {}:  {}:  {}:  {}:  {}:  {}:  {}{}:",
+                entity.name,
+                entity.cq,
+                entity.itu,
+                entity.continent,
+                entity.lat,
+                entity.lon,
+                hours_west(entity.utc_offset()),
+                if entity.waedc { "*" } else { "" },
+                entity.prefix
+            );
+            let aliases = aliases.collect::<Vec<_>>();
+            let _ = writeln!(out, "    {};", aliases.join(","));
+        }
+        out
+    }
+    // Write the runtime changes to a file, see `overrides_to_string`
+    #[cfg(feature = "fs")]
+    pub fn export_overrides<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), Box<dyn Error>> {
+        std::fs::write(path, self.overrides_to_string())?;
+        Ok(())
+    }
+}
@@ -0,0 +1,339 @@
+use crate::{dxcc, Continent, CqZone, Entity, ItuZone, Overrides, UtcOffset};
+use alloc::{
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::{error::Error, fmt};
+
+// Error with the 1-based line number of the country file it occurred on
+#[derive(Debug)]
+pub struct ParseError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl Error for ParseError {}
+
+// Every malformed record of a file, in line order
+#[derive(Debug)]
+pub struct ParseErrors {
+    pub errors: Vec<ParseError>,
+}
+
+impl fmt::Display for ParseErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} errors", self.errors.len())?;
+        for error in &self.errors {
+            write!(f, "\n{error}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for ParseErrors {}
+
+// Record skipped by lenient parsing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseWarning {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// A single item of the country file
+pub(crate) enum Record<'a> {
+    // Header line starting a new entity
+    Entity(Entity),
+    // Prefix or exact call of the last entity
+    Prefix {
+        pattern: &'a str,
+        is_exact: bool,
+        overrides: Overrides,
+        // The override tokens as written, e.g. "(15)[28]"
+        tokens: &'a str,
+    },
+}
+
+// The country file gives hours west of UTC
+fn get_timezone_offset(tz: &str) -> Result<UtcOffset, Box<dyn Error>> {
+    let tz = tz.parse::<f32>()?;
+    crate::offset_east(-(tz * 3600f32) as i32).ok_or("Invalid timezone".into())
+}
+
+// Characters opening an override token and the ones closing them
+const TOKENS: [(char, char); 5] = [('(', ')'), ('[', ']'), ('<', '>'), ('{', '}'), ('~', '~')];
+
+// Strict parsing rejects continents other than the seven of cty.dat
+fn continent(value: &str, lenient: bool) -> Result<Continent, Box<dyn Error>> {
+    match lenient {
+        true => Ok(Continent::parse_lenient(value)),
+        false => value.parse(),
+    }
+}
+
+// Parse the override tokens following a prefix, e.g. "(14)[28]<51.0/-10.0>{EU}~-1.0~",
+// in a single pass
+fn overrides(tokens: &str, lenient: bool) -> Result<Overrides, Box<dyn Error>> {
+    let mut overrides = Overrides::default();
+    let mut rest = tokens;
+    while let Some(open) = rest.chars().next() {
+        let Some(&(_, close)) = TOKENS.iter().find(|(o, _)| *o == open) else {
+            return Err(format!("Unexpected {open} in overrides {tokens}").into());
+        };
+        let Some((value, tail)) = rest[1..].split_once(close) else {
+            return Err(format!("Unterminated {open} in overrides {tokens}").into());
+        };
+        match open {
+            '(' => overrides.cq = Some(value.parse::<CqZone>()?),
+            '[' => overrides.itu = Some(value.parse::<ItuZone>()?),
+            '<' => {
+                let (lat, lon) = value
+                    .split_once('/')
+                    .ok_or_else(|| format!("Expected lat/lon, found {value}"))?;
+                overrides.lat = Some(lat.parse::<f32>()?);
+                overrides.lon = Some(lon.parse::<f32>()?);
+            }
+            '{' => overrides.continent = Some(continent(value, lenient)?),
+            _ => overrides.timezone = Some(get_timezone_offset(value)?),
+        }
+        rest = tail;
+    }
+    Ok(overrides)
+}
+
+struct Parser {
+    state: State,
+    lenient: bool,
+}
+
+// Records run from an entity header over alias lines up to a ;
+#[derive(PartialEq)]
+enum State {
+    // Before the first header or after the ; of the last record
+    Outside,
+    // Prefixes belong to the last entity header
+    Entity,
+    // The last entity header was skipped, and so are its prefixes
+    Skipping,
+}
+
+impl Parser {
+    fn new(lenient: bool) -> Parser {
+        Parser {
+            state: State::Outside,
+            lenient,
+        }
+    }
+
+    fn entity(&self, parts: &[&str]) -> Result<Entity, Box<dyn Error>> {
+        // The header ends with a colon
+        let parts = parts.strip_suffix(&[""]).unwrap_or(parts);
+        if parts.len() != 8 {
+            return Err(format!("Expected 8 fields, found {}", parts.len()).into());
+        }
+        let prefix = parts[7].trim_start_matches('*');
+        let dxcc = dxcc::dxcc_code(prefix);
+        Ok(Entity {
+            name: parts[0].to_string(),
+            cq: parts[1].parse::<CqZone>()?,
+            itu: parts[2].parse::<ItuZone>()?,
+            continent: continent(parts[3], self.lenient)?,
+            lat: parts[4].parse::<f32>()?,
+            lon: parts[5].parse::<f32>()?,
+            timezone: get_timezone_offset(parts[6])?,
+            prefix: prefix.to_string(),
+            waedc: parts[7].starts_with('*'),
+            dxcc,
+            id: Default::default(),
+            deleted: dxcc::deleted_dxcc_name(dxcc).is_some(),
+            most_wanted: None,
+            localized_names: Default::default(),
+        })
+    }
+
+    fn prefix<'a>(&self, alias: &'a str) -> Result<Record<'a>, Box<dyn Error>> {
+        let (is_exact, alias) = match alias.strip_prefix('=') {
+            Some(alias) => (true, alias),
+            None => (false, alias),
+        };
+        // The pattern ends at the first override token
+        let pos = alias
+            .find(|c| TOKENS.iter().any(|&(open, _)| open == c))
+            .unwrap_or(alias.len());
+        let tokens = &alias[pos..];
+        Ok(Record::Prefix {
+            pattern: &alias[..pos],
+            is_exact,
+            overrides: overrides(tokens, self.lenient)?,
+            tokens,
+        })
+    }
+
+    // Parse one line, in lenient mode bad records are skipped and reported to `warn`
+    fn line(
+        &mut self,
+        line: &str,
+        warn: &mut impl FnMut(Box<dyn Error>) -> Result<(), Box<dyn Error>>,
+        f: &mut impl FnMut(Record) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), Box<dyn Error>> {
+        // Only entity headers contain colons
+        if line.matches(':').nth(1).is_some() {
+            if self.state != State::Outside {
+                warn("Entity header inside the previous record, which is missing its ;".into())?;
+            }
+            let parts = line.split(':').map(str::trim).collect::<Vec<&str>>();
+            return match self.entity(&parts) {
+                Ok(entity) => {
+                    self.state = State::Entity;
+                    f(Record::Entity(entity))
+                }
+                Err(err) => {
+                    // Don't attach the prefixes of a skipped entity to the previous one
+                    self.state = State::Skipping;
+                    warn(err)
+                }
+            };
+        }
+        let (aliases, rest) = match line.split_once(';') {
+            Some((aliases, rest)) => (aliases, Some(rest)),
+            None => (line, None),
+        };
+        for alias in aliases.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            match self.state {
+                State::Outside => {
+                    warn(format!("Prefix {alias} outside of an entity record").into())?;
+                    continue;
+                }
+                State::Skipping => continue,
+                State::Entity => {}
+            }
+            match self.prefix(alias) {
+                Ok(record) => f(record)?,
+                Err(err) => warn(err)?,
+            }
+        }
+        if let Some(rest) = rest {
+            self.state = State::Outside;
+            let stray = rest
+                .split([',', ';'])
+                .map(str::trim)
+                .filter(|s| !s.is_empty());
+            for alias in stray {
+                warn(format!("Prefix {alias} outside of an entity record").into())?;
+            }
+        }
+        Ok(())
+    }
+
+    // The file may only end between records
+    fn finish(&self) -> Result<(), Box<dyn Error>> {
+        match self.state {
+            State::Outside => Ok(()),
+            _ => Err("Last record is missing its ;".into()),
+        }
+    }
+}
+
+// Parses a country file line by line, keeping the line numbers and warnings
+pub(crate) struct LineReader {
+    parser: Parser,
+    lenient: bool,
+    warnings: Vec<ParseWarning>,
+    lines: usize,
+}
+
+impl LineReader {
+    pub fn new(lenient: bool) -> LineReader {
+        LineReader {
+            parser: Parser::new(lenient),
+            lenient,
+            warnings: Vec::new(),
+            lines: 0,
+        }
+    }
+
+    // Feed every record of the next line to `f` together with its line number
+    pub fn line(
+        &mut self,
+        line: Result<&str, Box<dyn Error>>,
+        f: &mut impl FnMut(usize, Record) -> Result<(), Box<dyn Error>>,
+    ) -> Result<(), ParseError> {
+        self.lines += 1;
+        let number = self.lines;
+        let (lenient, warnings) = (self.lenient, &mut self.warnings);
+        let mut warn = |err: Box<dyn Error>| {
+            if !lenient {
+                return Err(err);
+            }
+            warnings.push(ParseWarning {
+                line: number,
+                message: err.to_string(),
+            });
+            Ok(())
+        };
+        match line {
+            Ok(line) => {
+                // Byte order marks survive decoding of some editors' files
+                let line = match number {
+                    1 => line.trim_start_matches('\u{feff}'),
+                    _ => line,
+                };
+                self.parser
+                    .line(line, &mut warn, &mut |record| f(number, record))
+            }
+            Err(err) => warn(err),
+        }
+        .map_err(|err| ParseError {
+            line: number,
+            message: err.to_string(),
+        })
+    }
+
+    pub fn finish(mut self) -> Result<Vec<ParseWarning>, ParseError> {
+        if let Err(err) = self.parser.finish() {
+            let line = self.lines;
+            if !self.lenient {
+                return Err(ParseError {
+                    line,
+                    message: err.to_string(),
+                });
+            }
+            self.warnings.push(ParseWarning {
+                line,
+                message: err.to_string(),
+            });
+        }
+        Ok(self.warnings)
+    }
+}
+
+// Feed every record of the lines to `f` together with its line number.
+// Strict parsing stops at the first error, lenient parsing skips bad records
+// and returns them as warnings.
+pub(crate) fn parse_lines<L: AsRef<str>, E: Into<Box<dyn Error>>>(
+    lines: impl Iterator<Item = Result<L, E>>,
+    lenient: bool,
+    mut f: impl FnMut(usize, Record) -> Result<(), Box<dyn Error>>,
+) -> Result<Vec<ParseWarning>, ParseError> {
+    let mut reader = LineReader::new(lenient);
+    for line in lines {
+        match line {
+            Ok(line) => reader.line(Ok(line.as_ref()), &mut f)?,
+            Err(err) => reader.line(Err(err.into()), &mut f)?,
+        }
+    }
+    reader.finish()
+}
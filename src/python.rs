@@ -0,0 +1,112 @@
+// Python module, built with maturin (see pyproject.toml)
+use crate::{Cty, Entity};
+use pyo3::{exceptions::PyValueError, prelude::*};
+use std::{error::Error, io};
+
+// I/O errors become OSError, everything else ValueError
+fn to_py_err(err: Box<dyn Error>) -> PyErr {
+    match err.downcast::<io::Error>() {
+        Ok(err) => (*err).into(),
+        Err(err) => PyValueError::new_err(err.to_string()),
+    }
+}
+
+#[pyclass(
+    name = "Entity",
+    module = "cty_rs",
+    frozen,
+    get_all,
+    eq,
+    skip_from_py_object
+)]
+#[derive(Clone, PartialEq)]
+struct PyEntity {
+    name: String,
+    cq: u32,
+    itu: u32,
+    continent: String,
+    lat: f32,
+    // Positive west, as in the country file
+    lon: f32,
+    // Hours east of UTC
+    utc_offset: f32,
+    prefix: String,
+    waedc: bool,
+    dxcc: u32,
+}
+
+impl From<&Entity> for PyEntity {
+    fn from(entity: &Entity) -> PyEntity {
+        PyEntity {
+            name: entity.name.clone(),
+            cq: entity.cq.into(),
+            itu: entity.itu.into(),
+            continent: entity.continent.to_string(),
+            lat: entity.lat,
+            lon: entity.lon,
+            utc_offset: entity.utc_offset() as f32 / 3600.0,
+            prefix: entity.prefix.clone(),
+            waedc: entity.waedc,
+            dxcc: entity.dxcc,
+        }
+    }
+}
+
+#[pymethods]
+impl PyEntity {
+    fn __repr__(&self) -> String {
+        format!(
+            "Entity(name={:?}, cq={}, itu={}, continent={:?}, lat={}, lon={}, utc_offset={}, prefix={:?}, waedc={}, dxcc={})",
+            self.name,
+            self.cq,
+            self.itu,
+            self.continent,
+            self.lat,
+            self.lon,
+            self.utc_offset,
+            self.prefix,
+            if self.waedc { "True" } else { "False" },
+            self.dxcc
+        )
+    }
+}
+
+#[pyclass(name = "Cty", module = "cty_rs", frozen)]
+struct PyCty {
+    cty: Cty,
+}
+
+#[pymethods]
+impl PyCty {
+    #[new]
+    fn new(filename: &str) -> PyResult<PyCty> {
+        Ok(PyCty {
+            cty: Cty::new(filename).map_err(to_py_err)?,
+        })
+    }
+
+    // Parse the contents of a country file
+    #[staticmethod]
+    fn from_str(data: &str) -> PyResult<PyCty> {
+        Ok(PyCty {
+            cty: data.parse().map_err(to_py_err)?,
+        })
+    }
+
+    fn lookup(&self, callsign: &str) -> Option<PyEntity> {
+        self.cty
+            .lookup(&callsign.to_ascii_uppercase())
+            .map(PyEntity::from)
+    }
+
+    fn entities(&self) -> Vec<PyEntity> {
+        self.cty.entities().map(PyEntity::from).collect()
+    }
+}
+
+#[pymodule]
+fn cty_rs(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyCty>()?;
+    m.add_class::<PyEntity>()?;
+    Ok(())
+}
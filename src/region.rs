@@ -0,0 +1,111 @@
+// Finer location hints for entities whose call area digits have a fixed
+// geographical meaning, e.g. the W1 to W0 areas of the United States or the
+// districts of Russia
+use crate::{callsign, CqZone, Cty, ItuZone};
+
+// (entity primary prefix, call area digit or WPX prefix, region)
+const REGIONS: &[(&str, &str, &str)] = &[
+    ("K", "1", "New England"),
+    ("K", "2", "New York and New Jersey"),
+    ("K", "3", "Pennsylvania, Delaware, Maryland and DC"),
+    ("K", "4", "Southeast"),
+    ("K", "5", "South Central"),
+    ("K", "6", "California"),
+    ("K", "7", "Northwest"),
+    ("K", "8", "Michigan, Ohio and West Virginia"),
+    ("K", "9", "Illinois, Indiana and Wisconsin"),
+    ("K", "0", "Central Plains"),
+    ("VE", "VE1", "Nova Scotia"),
+    ("VE", "VA1", "Nova Scotia"),
+    ("VE", "VE2", "Quebec"),
+    ("VE", "VA2", "Quebec"),
+    ("VE", "VE3", "Ontario"),
+    ("VE", "VA3", "Ontario"),
+    ("VE", "VE4", "Manitoba"),
+    ("VE", "VA4", "Manitoba"),
+    ("VE", "VE5", "Saskatchewan"),
+    ("VE", "VA5", "Saskatchewan"),
+    ("VE", "VE6", "Alberta"),
+    ("VE", "VA6", "Alberta"),
+    ("VE", "VE7", "British Columbia"),
+    ("VE", "VA7", "British Columbia"),
+    ("VE", "VE8", "Northwest Territories"),
+    ("VE", "VE9", "New Brunswick"),
+    ("VE", "VO1", "Newfoundland"),
+    ("VE", "VO2", "Labrador"),
+    ("VE", "VY0", "Nunavut"),
+    ("VE", "VY1", "Yukon"),
+    ("VE", "VY2", "Prince Edward Island"),
+    ("VK", "1", "Australian Capital Territory"),
+    ("VK", "2", "New South Wales"),
+    ("VK", "3", "Victoria"),
+    ("VK", "4", "Queensland"),
+    ("VK", "5", "South Australia"),
+    ("VK", "6", "Western Australia"),
+    ("VK", "7", "Tasmania"),
+    ("VK", "8", "Northern Territory"),
+    ("JA", "1", "Kanto"),
+    ("JA", "2", "Tokai"),
+    ("JA", "3", "Kansai"),
+    ("JA", "4", "Chugoku"),
+    ("JA", "5", "Shikoku"),
+    ("JA", "6", "Kyushu and Okinawa"),
+    ("JA", "7", "Tohoku"),
+    ("JA", "8", "Hokkaido"),
+    ("JA", "9", "Hokuriku"),
+    ("JA", "0", "Shinetsu"),
+    // Call districts of Russia, the oblast is given by the first letter of
+    // the suffix as well. European Russia keeps 8 and 9 for the Perm and
+    // Komi calls of the Urals.
+    ("UA", "1", "Northwest"),
+    ("UA", "3", "Central"),
+    ("UA", "4", "Volga"),
+    ("UA", "6", "South and North Caucasus"),
+    ("UA", "8", "Perm and Komi"),
+    ("UA", "9", "Perm and Komi"),
+    ("UA9", "8", "Urals and Siberia"),
+    ("UA9", "9", "Urals and Siberia"),
+    ("UA9", "0", "East Siberia and Far East"),
+];
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Region {
+    // Last digit of the call's WPX prefix, 4 for W4ABC and 7 for K1AA/7
+    pub call_area: Option<u8>,
+    // The call area or province, for entities where the digit has a fixed meaning
+    pub name: Option<&'static str>,
+    // Zones of the call, per-prefix overrides move them off the entity's
+    pub cq: CqZone,
+    pub itu: ItuZone,
+    pub zones_adjusted: bool,
+}
+
+impl Cty {
+    // Where inside its entity a call is, None if it doesn't resolve
+    pub fn region(&self, callsign: &str) -> Option<Region> {
+        let entity = self.lookup(callsign)?;
+        let wpx = callsign::wpx_prefix(callsign);
+        let call_area = wpx
+            .as_deref()
+            .and_then(|p| p.bytes().last())
+            .filter(u8::is_ascii_digit)
+            .map(|b| b - b'0');
+        let name = REGIONS
+            .iter()
+            .filter(|(prefix, _, _)| *prefix == entity.prefix)
+            .find(|(_, key, _)| match key.len() {
+                1 => call_area.is_some_and(|area| key.as_bytes()[0] - b'0' == area),
+                _ => wpx.as_deref() == Some(*key),
+            })
+            .map(|(_, _, name)| *name);
+        let home = self.entities().find(|e| e.prefix == entity.prefix);
+        Some(Region {
+            call_area,
+            name,
+            cq: entity.cq,
+            itu: entity.itu,
+            zones_adjusted: home.is_some_and(|e| e.cq != entity.cq || e.itu != entity.itu),
+        })
+    }
+}
@@ -0,0 +1,74 @@
+// Common interface of the databases callsigns can be resolved with, so
+// applications can put their own sources in front of or behind cty.dat
+use crate::{Cty, Entity};
+use alloc::{boxed::Box, sync::Arc, vec::Vec};
+
+pub trait CallsignResolver {
+    fn resolve(&self, callsign: &str) -> Option<Arc<Entity>>;
+}
+
+impl CallsignResolver for Cty {
+    fn resolve(&self, callsign: &str) -> Option<Arc<Entity>> {
+        self.lookup_owned(callsign)
+    }
+}
+
+#[cfg(feature = "service")]
+impl CallsignResolver for crate::CtyService {
+    fn resolve(&self, callsign: &str) -> Option<Arc<Entity>> {
+        self.lookup(callsign)
+    }
+}
+
+#[cfg(feature = "watch")]
+impl CallsignResolver for crate::WatchedCty {
+    fn resolve(&self, callsign: &str) -> Option<Arc<Entity>> {
+        self.lookup(callsign)
+    }
+}
+
+// Adapters to other sources can be plain functions or closures
+impl<F: Fn(&str) -> Option<Arc<Entity>>> CallsignResolver for F {
+    fn resolve(&self, callsign: &str) -> Option<Arc<Entity>> {
+        self(callsign)
+    }
+}
+
+impl<R: CallsignResolver + ?Sized> CallsignResolver for Arc<R> {
+    fn resolve(&self, callsign: &str) -> Option<Arc<Entity>> {
+        (**self).resolve(callsign)
+    }
+}
+
+// Resolvers tried in order, the first one knowing the call wins, e.g. local
+// exceptions, then cty.dat, then an online callbook
+#[derive(Default)]
+pub struct ChainedResolver {
+    resolvers: Vec<Box<dyn CallsignResolver + Send + Sync>>,
+}
+
+impl ChainedResolver {
+    pub fn new() -> ChainedResolver {
+        ChainedResolver::default()
+    }
+    // Add a resolver to try after the previous ones
+    pub fn with(
+        mut self,
+        resolver: impl CallsignResolver + Send + Sync + 'static,
+    ) -> ChainedResolver {
+        self.resolvers.push(Box::new(resolver));
+        self
+    }
+    pub fn len(&self) -> usize {
+        self.resolvers.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.resolvers.is_empty()
+    }
+}
+
+impl CallsignResolver for ChainedResolver {
+    fn resolve(&self, callsign: &str) -> Option<Arc<Entity>> {
+        self.resolvers.iter().find_map(|r| r.resolve(callsign))
+    }
+}
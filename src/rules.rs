@@ -0,0 +1,100 @@
+// Corrections for calls the prefixes alone resolve to the wrong entity
+use crate::Entity;
+use alloc::{
+    string::{String, ToString},
+    sync::Arc,
+    vec::Vec,
+};
+use core::fmt;
+
+type RuleFn = dyn Fn(&str, &Entity) -> Option<String> + Send + Sync;
+
+// Runs after a callsign matched a (non-exact) prefix, returning the primary
+// prefix of the entity the call really belongs to
+#[derive(Clone)]
+pub struct Rule {
+    kind: Kind,
+}
+
+#[derive(Clone)]
+enum Kind {
+    Pattern { pattern: String, prefix: String },
+    Custom(Arc<RuleFn>),
+}
+
+impl fmt::Debug for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            Kind::Pattern { pattern, prefix } => write!(f, "Rule({pattern} -> {prefix})"),
+            Kind::Custom(_) => write!(f, "Rule(custom)"),
+        }
+    }
+}
+
+// Match a whole callsign against a pattern where @ is a letter, # a digit,
+// ? any character and * any run of characters
+pub(crate) fn matches(pattern: &[u8], call: &[u8]) -> bool {
+    match (pattern.first(), call.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) => {
+            matches(&pattern[1..], call) || (!call.is_empty() && matches(pattern, &call[1..]))
+        }
+        (Some(&p), Some(&c)) => {
+            let hit = match p {
+                b'@' => c.is_ascii_alphabetic(),
+                b'#' => c.is_ascii_digit(),
+                b'?' => true,
+                _ => p.eq_ignore_ascii_case(&c),
+            };
+            hit && matches(&pattern[1..], &call[1..])
+        }
+        _ => false,
+    }
+}
+
+impl Rule {
+    // Calls matching the pattern belong to the entity with the primary prefix
+    pub fn pattern(pattern: &str, prefix: &str) -> Rule {
+        Rule {
+            kind: Kind::Pattern {
+                pattern: pattern.to_string(),
+                prefix: prefix.to_string(),
+            },
+        }
+    }
+
+    // Decide from the call (without portable designators) and the entity its prefix resolved to
+    pub fn custom(f: impl Fn(&str, &Entity) -> Option<String> + Send + Sync + 'static) -> Rule {
+        Rule {
+            kind: Kind::Custom(Arc::new(f)),
+        }
+    }
+
+    // The pattern and primary prefix of a pattern rule
+    #[cfg(feature = "codegen")]
+    pub(crate) fn as_pattern(&self) -> Option<(&str, &str)> {
+        match &self.kind {
+            Kind::Pattern { pattern, prefix } => Some((pattern, prefix)),
+            Kind::Custom(_) => None,
+        }
+    }
+
+    pub(crate) fn apply(&self, call: &str, entity: &Entity) -> Option<String> {
+        match &self.kind {
+            Kind::Pattern { pattern, prefix } => {
+                matches(pattern.as_bytes(), call.as_bytes()).then(|| prefix.clone())
+            }
+            Kind::Custom(f) => f(call, entity),
+        }
+    }
+}
+
+// Well-known special cases, the first matching rule wins
+pub(crate) fn builtin() -> Vec<Rule> {
+    Vec::from([
+        // Only KG4 calls with a two letter suffix are Guantanamo Bay,
+        // the others are issued in the continental US
+        Rule::pattern("KG4@@", "KG4"),
+        Rule::pattern("KG4*", "K"),
+    ])
+}
@@ -0,0 +1,80 @@
+// A database shared between threads and refreshed in the background
+use crate::{Cty, Entity};
+use arc_swap::ArcSwap;
+use std::{
+    error::Error,
+    sync::{Arc, Weak},
+    thread,
+    time::Duration,
+};
+
+type Loader = dyn Fn() -> Result<Cty, Box<dyn Error>> + Send + Sync;
+
+// Cheaply cloneable handle to a database that can be swapped atomically.
+// Readers take snapshots, which stay valid while a newer database is
+// swapped in. With a loader, the database can be reloaded on demand or
+// periodically by a background thread that stops once every handle is dropped.
+#[derive(Clone)]
+pub struct CtyService {
+    pub(crate) cty: Arc<ArcSwap<Cty>>,
+    loader: Option<Arc<Loader>>,
+}
+
+impl CtyService {
+    // Share an already loaded database, later replaced with `replace`
+    pub fn new(cty: Cty) -> CtyService {
+        CtyService {
+            cty: Arc::new(ArcSwap::from_pointee(cty)),
+            loader: None,
+        }
+    }
+
+    // Load the database with `loader`, e.g. one downloading the latest
+    // country file, and call it again every `refresh` if given. A failed
+    // refresh keeps the previous data until the next one.
+    pub fn with_loader(
+        loader: impl Fn() -> Result<Cty, Box<dyn Error>> + Send + Sync + 'static,
+        refresh: Option<Duration>,
+    ) -> Result<CtyService, Box<dyn Error>> {
+        let loader: Arc<Loader> = Arc::new(loader);
+        let mut service = CtyService::new(loader()?);
+        if let Some(interval) = refresh {
+            let weak: Weak<ArcSwap<Cty>> = Arc::downgrade(&service.cty);
+            let loader = loader.clone();
+            thread::spawn(move || loop {
+                thread::sleep(interval);
+                let Some(cty) = weak.upgrade() else {
+                    break;
+                };
+                if let Ok(reloaded) = loader() {
+                    cty.store(Arc::new(reloaded));
+                }
+            });
+        }
+        service.loader = Some(loader);
+        Ok(service)
+    }
+
+    // Run the loader now, keeping the current data if it fails
+    pub fn reload(&self) -> Result<(), Box<dyn Error>> {
+        let Some(loader) = &self.loader else {
+            return Err("No loader to reload with".into());
+        };
+        self.replace(loader()?);
+        Ok(())
+    }
+
+    // Swap in another database for every handle
+    pub fn replace(&self, cty: Cty) {
+        self.cty.store(Arc::new(cty));
+    }
+
+    // Snapshot of the current database
+    pub fn current(&self) -> Arc<Cty> {
+        self.cty.load_full()
+    }
+
+    pub fn lookup(&self, callsign: &str) -> Option<Arc<Entity>> {
+        self.cty.load().lookup_owned(callsign)
+    }
+}
@@ -0,0 +1,92 @@
+// A database in static data, generated at build time by `codegen` so looking
+// up calls needs neither a file nor any parsing at startup
+use crate::{callsign, rules};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StaticEntity {
+    pub name: &'static str,
+    pub prefix: &'static str,
+    pub dxcc: u16,
+    pub id: u16,
+    pub waedc: bool,
+    pub deleted: bool,
+}
+
+// A prefix or exact call with the values of its entity, overrides applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StaticPrefix {
+    pub pattern: &'static str,
+    pub exact: bool,
+    pub entity: &'static StaticEntity,
+    pub cq: u8,
+    pub itu: u8,
+    pub continent: &'static str,
+    pub lat: f32,
+    // Positive west like `Entity::lon`
+    pub lon: f32,
+    // Seconds east of UTC
+    pub utc_offset: i32,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct StaticCty {
+    pub version: Option<&'static str>,
+    pub entities: &'static [StaticEntity],
+    // Sorted by pattern
+    pub prefixes: &'static [StaticPrefix],
+    // Pattern rules and the primary prefix record of the entity they give
+    pub rules: &'static [(&'static str, &'static StaticPrefix)],
+}
+
+impl StaticCty {
+    fn get(&self, pattern: &str) -> Option<&'static StaticPrefix> {
+        let prefixes = self.prefixes;
+        let i = prefixes.binary_search_by(|p| p.pattern.cmp(pattern)).ok()?;
+        Some(&prefixes[i])
+    }
+
+    fn find_prefix(&self, call: &str) -> Option<&'static StaticPrefix> {
+        if let Some(prefix) = self.get(call).filter(|p| p.exact) {
+            return Some(prefix);
+        }
+        (1..=call.len())
+            .rev()
+            .filter(|&i| call.is_char_boundary(i))
+            .find_map(|i| self.get(&call[..i]).filter(|p| !p.exact))
+    }
+
+    // Resolve a callsign like `Cty::lookup` with the default options, custom
+    // rules of the database the data was generated from aside
+    pub fn lookup(&self, callsign: &str) -> Option<&'static StaticPrefix> {
+        if let Some(prefix) = self.get(callsign).filter(|p| p.exact) {
+            return Some(prefix);
+        }
+        if !callsign.is_ascii() {
+            return None;
+        }
+        let parts = callsign::split(callsign);
+        let relocated = parts
+            .call_area()
+            .map(|area| callsign::relocate(parts.base, area));
+        let (call, home) = match (&relocated, parts.location) {
+            (Some(relocated), _) => (relocated.as_str(), Some(relocated.as_str())),
+            (None, Some(location)) => (location, None),
+            (None, None) => (parts.base, Some(parts.base)),
+        };
+        let (home, prefix) = match self.find_prefix(call) {
+            Some(prefix) => (home, prefix),
+            None if home.is_none() => (Some(parts.base), self.find_prefix(parts.base)?),
+            None => return None,
+        };
+        if let Some(call) = home.filter(|_| !prefix.exact) {
+            let rule = self
+                .rules
+                .iter()
+                .find(|(pattern, _)| rules::matches(pattern.as_bytes(), call.as_bytes()));
+            if let Some(&(_, target)) = rule {
+                return Some(target);
+            }
+        }
+        Some(prefix)
+    }
+}
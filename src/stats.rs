@@ -0,0 +1,89 @@
+// Summary counts of a loaded database, for sanity checks and status lines
+use crate::{Continent, CqZone, Cty, ItuZone};
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    string::{String, ToString},
+};
+use core::fmt;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats {
+    pub entities: usize,
+    // Prefixes other than exact calls
+    pub prefixes: usize,
+    pub exact_calls: usize,
+    // WAE-only entities like Sicily, included in `entities`
+    pub waedc_entities: usize,
+    pub deleted_entities: usize,
+    // Entities by their own continent, prefix overrides aside
+    pub by_continent: BTreeMap<Continent, usize>,
+    // Zones at least one prefix resolves to
+    pub cq_zones: BTreeSet<CqZone>,
+    pub itu_zones: BTreeSet<ItuZone>,
+}
+
+impl Stats {
+    // CQ zones 1 to 40 no prefix resolves to
+    pub fn missing_cq_zones(&self) -> impl Iterator<Item = CqZone> + '_ {
+        CqZone::all().filter(|zone| !self.cq_zones.contains(zone))
+    }
+    // ITU zones 1 to 90 no prefix resolves to
+    pub fn missing_itu_zones(&self) -> impl Iterator<Item = ItuZone> + '_ {
+        ItuZone::all().filter(|zone| !self.itu_zones.contains(zone))
+    }
+}
+
+// 27312 as 27,312
+fn grouped(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+// e.g. "346 entities, 27,312 prefixes, 19,030 exact calls"
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entities, {} prefixes, {} exact calls",
+            grouped(self.entities),
+            grouped(self.prefixes),
+            grouped(self.exact_calls)
+        )
+    }
+}
+
+impl Cty {
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats {
+            entities: self.entities.len(),
+            cq_zones: self.cq_zones.keys().copied().collect(),
+            itu_zones: self.itu_zones.keys().copied().collect(),
+            ..Stats::default()
+        };
+        for prefix in self.prefixes() {
+            match prefix.is_exact {
+                true => stats.exact_calls += 1,
+                false => stats.prefixes += 1,
+            }
+        }
+        for entity in self.entities() {
+            stats.waedc_entities += entity.waedc as usize;
+            stats.deleted_entities += entity.deleted as usize;
+            if entity.continent.is_known() {
+                *stats
+                    .by_continent
+                    .entry(entity.continent.clone())
+                    .or_default() += 1;
+            }
+        }
+        stats
+    }
+}
@@ -0,0 +1,183 @@
+// Building a database record by record, and a push parser on top of it for
+// files that arrive in chunks, e.g. while they download
+use crate::{
+    dxcc, encoding, parser::LineReader, rules, table::ResolvedCache, Cty, CtyBuilder, Entity,
+    Longitude, Overrides, ParseError, ParseErrors, ParseWarning, Prefix, Record,
+};
+use alloc::{boxed::Box, string::ToString, sync::Arc, vec::Vec};
+use core::error::Error;
+
+// The database under construction
+pub(crate) struct Loader {
+    cty: Cty,
+    east: bool,
+    // Fail with every error at the end instead of on the first one
+    all_errors: bool,
+    last_entity: Arc<Entity>,
+    resolved: ResolvedCache,
+}
+
+impl Loader {
+    pub fn new(options: &CtyBuilder) -> Loader {
+        Loader {
+            cty: Cty {
+                rules: rules::builtin(),
+                normalize_calls: options.normalize_calls,
+                portable_calls: options.portable_calls,
+                ..Cty::default()
+            },
+            east: options.longitude == Longitude::East,
+            all_errors: options.all_errors && !options.lenient,
+            last_entity: Arc::new(Entity::default()),
+            resolved: ResolvedCache::default(),
+        }
+    }
+
+    // Whether the parser should carry on past malformed records
+    pub fn lenient(options: &CtyBuilder) -> bool {
+        options.lenient || options.all_errors
+    }
+
+    pub fn record(&mut self, record: Record) {
+        let cty = &mut self.cty;
+        match record {
+            Record::Entity(mut entity) => {
+                if self.east {
+                    entity.lon = -entity.lon;
+                }
+                entity.id = dxcc::entity_id(
+                    &entity.prefix,
+                    entity.waedc,
+                    entity.dxcc,
+                    cty.entities.len(),
+                );
+                self.last_entity = Arc::new(entity);
+                cty.entities.push(self.last_entity.clone());
+                cty.insert_prefix(Prefix::new(
+                    &self.last_entity.prefix,
+                    false,
+                    Overrides::default(),
+                    &self.last_entity,
+                    cty.entities.len() - 1,
+                ));
+            }
+            Record::Prefix {
+                pattern,
+                is_exact,
+                mut overrides,
+                tokens,
+            } => {
+                if self.east {
+                    overrides.lon = overrides.lon.map(|lon| -lon);
+                }
+                let index = cty.entities.len() - 1;
+                let record = self.resolved.get(&self.last_entity, index, &overrides);
+                cty.insert_prefix(Prefix {
+                    tokens: tokens.to_string(),
+                    ..Prefix::with_resolved(
+                        pattern,
+                        is_exact,
+                        overrides,
+                        &self.last_entity,
+                        index,
+                        record,
+                    )
+                });
+            }
+        }
+    }
+
+    pub fn finish(
+        self,
+        warnings: Vec<ParseWarning>,
+    ) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        if self.all_errors && !warnings.is_empty() {
+            let errors = warnings
+                .into_iter()
+                .map(|w| ParseError {
+                    line: w.line,
+                    message: w.message,
+                })
+                .collect();
+            return Err(Box::new(ParseErrors { errors }));
+        }
+        let mut cty = self.cty;
+        cty.build_indexes();
+        Ok((cty, warnings))
+    }
+}
+
+// Push parser for a country file arriving in chunks. Lines are decoded one at
+// a time, as UTF-8 or with the encoding feature Windows-1252, UTF-16 files
+// need `CtyBuilder::parse_bytes`.
+pub struct Parser {
+    loader: Loader,
+    reader: LineReader,
+    // Start of a line whose end hasn't arrived yet
+    pending: Vec<u8>,
+}
+
+impl Default for Parser {
+    fn default() -> Parser {
+        Parser::new()
+    }
+}
+
+impl Parser {
+    pub fn new() -> Parser {
+        CtyBuilder::new().parser()
+    }
+
+    fn line(&mut self, line: &[u8]) -> Result<(), ParseError> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let loader = &mut self.loader;
+        let mut f = |_, record: Record| {
+            loader.record(record);
+            Ok(())
+        };
+        match encoding::decode(line) {
+            Ok(text) => self.reader.line(Ok(&text), &mut f),
+            Err(err) => self.reader.line(Err(err), &mut f),
+        }
+    }
+
+    // Parse the complete lines of the next chunk. Strict parsing fails on the
+    // first malformed record, the parser shouldn't be fed after that.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), ParseError> {
+        let mut rest = chunk;
+        while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+            let (line, tail) = (&rest[..end], &rest[end + 1..]);
+            if self.pending.is_empty() {
+                self.line(line)?;
+            } else {
+                let mut pending = core::mem::take(&mut self.pending);
+                pending.extend_from_slice(line);
+                self.line(&pending)?;
+            }
+            rest = tail;
+        }
+        self.pending.extend_from_slice(rest);
+        Ok(())
+    }
+
+    // Parse the last line and build the database
+    pub fn finish(mut self) -> Result<(Cty, Vec<ParseWarning>), Box<dyn Error>> {
+        if !self.pending.is_empty() {
+            let pending = core::mem::take(&mut self.pending);
+            self.line(&pending)?;
+        }
+        self.loader.finish(self.reader.finish()?)
+    }
+}
+
+impl CtyBuilder {
+    // A push parser with this configuration, like `parse` it ignores the
+    // added files
+    pub fn parser(&self) -> Parser {
+        Parser {
+            loader: Loader::new(self),
+            reader: LineReader::new(Loader::lenient(self)),
+            pending: Vec::new(),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+// Did-you-mean suggestions for calls that don't resolve, e.g. busted calls
+// in contest logs
+use crate::{Cty, Prefix};
+use alloc::{vec, vec::Vec};
+
+// Edit distance between a pattern and the call. Prefixes only have to match
+// the start of the call, exact calls the whole of it.
+fn distance(pattern: &[u8], call: &[u8], exact: bool) -> usize {
+    // One row per pattern character, one column per call character
+    let mut row = (0..=call.len()).collect::<Vec<_>>();
+    for (i, &p) in pattern.iter().enumerate() {
+        let mut next = vec![i + 1; call.len() + 1];
+        for (j, &c) in call.iter().enumerate() {
+            let substitution = row[j] + usize::from(p != c);
+            next[j + 1] = substitution.min(row[j + 1] + 1).min(next[j] + 1);
+        }
+        row = next;
+    }
+    match exact {
+        true => row[call.len()],
+        false => row.into_iter().min().unwrap_or_default(),
+    }
+}
+
+impl Cty {
+    // The `n` prefixes and exact calls closest to a callsign by edit distance,
+    // closest first, with longer patterns first among equally close ones
+    pub fn suggest(&self, callsign: &str, n: usize) -> Vec<(&Prefix, usize)> {
+        let call = callsign.trim().to_ascii_uppercase();
+        let mut suggestions = self
+            .prefixes()
+            .map(|p| {
+                let distance = distance(p.pattern.as_bytes(), call.as_bytes(), p.is_exact);
+                (p, distance)
+            })
+            .collect::<Vec<_>>();
+        suggestions.sort_by(|(a, da), (b, db)| {
+            da.cmp(db)
+                .then_with(|| b.pattern.len().cmp(&a.pattern.len()))
+                .then_with(|| a.pattern.cmp(&b.pattern))
+        });
+        suggestions.truncate(n);
+        suggestions
+    }
+}
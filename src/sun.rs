@@ -0,0 +1,172 @@
+// Sunrise and sunset from the sunrise equation, accurate to about a minute.
+// Coordinates are in degrees with north latitude and east longitude positive.
+use chrono::{DateTime, Duration, FixedOffset, NaiveDate, Utc};
+
+// Altitude of the sun's center at sunrise/sunset, accounting for refraction and its radius
+pub const SUNRISE_ALTITUDE: f64 = -0.833;
+
+const J2000: f64 = 2451545.0;
+const UNIX_EPOCH_JD: f64 = 2440587.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunTimes {
+    // None when the sun doesn't rise (polar night) or set (midnight sun) on that day
+    pub sunrise: Option<DateTime<Utc>>,
+    pub sunset: Option<DateTime<Utc>>,
+    pub timezone: FixedOffset,
+}
+
+impl SunTimes {
+    pub fn sunrise_local(&self) -> Option<DateTime<FixedOffset>> {
+        self.sunrise.map(|t| t.with_timezone(&self.timezone))
+    }
+    pub fn sunset_local(&self) -> Option<DateTime<FixedOffset>> {
+        self.sunset.map(|t| t.with_timezone(&self.timezone))
+    }
+}
+
+fn from_julian(jd: f64) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp_millis(((jd - UNIX_EPOCH_JD) * 86_400_000.0).round() as i64)
+}
+
+// Times the sun's center crosses the given altitude on the way up and down
+pub fn crossings(
+    lat: f64,
+    lon: f64,
+    date: NaiveDate,
+    altitude: f64,
+) -> (Option<DateTime<Utc>>, Option<DateTime<Utc>>) {
+    let noon = date.and_hms_opt(12, 0, 0).unwrap().and_utc().timestamp() as f64;
+    let n = (noon / 86_400.0 + UNIX_EPOCH_JD - J2000).round() + 0.0008;
+    // Mean solar noon
+    let mean_noon = n - lon / 360.0;
+    let anomaly = (357.5291 + 0.98560028 * mean_noon)
+        .rem_euclid(360.0)
+        .to_radians();
+    let center =
+        1.9148 * anomaly.sin() + 0.02 * (2.0 * anomaly).sin() + 0.0003 * (3.0 * anomaly).sin();
+    let ecliptic_lon = (anomaly.to_degrees() + center + 180.0 + 102.9372)
+        .rem_euclid(360.0)
+        .to_radians();
+    let transit = J2000 + mean_noon + 0.0053 * anomaly.sin() - 0.0069 * (2.0 * ecliptic_lon).sin();
+    let declination = (ecliptic_lon.sin() * 23.4397f64.to_radians().sin()).asin();
+    let lat = lat.to_radians();
+    let cos_hour_angle = (altitude.to_radians().sin() - lat.sin() * declination.sin())
+        / (lat.cos() * declination.cos());
+    if !(-1.0..=1.0).contains(&cos_hour_angle) {
+        return (None, None);
+    }
+    let hour_angle = cos_hour_angle.acos().to_degrees();
+    (
+        from_julian(transit - hour_angle / 360.0),
+        from_julian(transit + hour_angle / 360.0),
+    )
+}
+
+pub fn sun_times(lat: f64, lon: f64, date: NaiveDate, timezone: FixedOffset) -> SunTimes {
+    let (sunrise, sunset) = crossings(lat, lon, date, SUNRISE_ALTITUDE);
+    SunTimes {
+        sunrise,
+        sunset,
+        timezone,
+    }
+}
+
+// Where the sun stands in the sky, in degrees. Azimuth is clockwise from
+// true north, elevation above the horizon without refraction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunPosition {
+    pub azimuth: f64,
+    pub elevation: f64,
+}
+
+impl SunPosition {
+    pub fn is_daylight(&self) -> bool {
+        self.elevation > SUNRISE_ALTITUDE
+    }
+}
+
+// Low precision solar coordinates from the Astronomical Almanac, good to
+// about a hundredth of a degree until 2050
+pub fn sun_position(lat: f64, lon: f64, time: DateTime<Utc>) -> SunPosition {
+    let d = time.timestamp_millis() as f64 / 86_400_000.0 + UNIX_EPOCH_JD - J2000;
+    let mean_lon = 280.460 + 0.9856474 * d;
+    let anomaly = (357.528 + 0.9856003 * d).to_radians();
+    let ecliptic_lon =
+        (mean_lon + 1.915 * anomaly.sin() + 0.020 * (2.0 * anomaly).sin()).to_radians();
+    let obliquity = (23.439 - 0.0000004 * d).to_radians();
+    let right_ascension = (obliquity.cos() * ecliptic_lon.sin()).atan2(ecliptic_lon.cos());
+    let declination = (obliquity.sin() * ecliptic_lon.sin()).asin();
+    // Greenwich mean sidereal time in degrees
+    let sidereal = 280.46061837 + 360.98564736629 * d;
+    let hour_angle = (sidereal + lon).to_radians() - right_ascension;
+    let lat = lat.to_radians();
+    let elevation =
+        (lat.sin() * declination.sin() + lat.cos() * declination.cos() * hour_angle.cos()).asin();
+    let azimuth =
+        (-hour_angle.sin()).atan2(declination.tan() * lat.cos() - lat.sin() * hour_angle.cos());
+    SunPosition {
+        azimuth: azimuth.to_degrees().rem_euclid(360.0),
+        elevation: elevation.to_degrees(),
+    }
+}
+
+// A stretch of time both ends of a path are near their terminator
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GrayLine {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    // Whether each end is around its sunrise rather than its sunset
+    pub from_sunrise: bool,
+    pub to_sunrise: bool,
+}
+
+// Times within `margin` of sunrise and sunset at a position on the days
+// around `date`, with whether each is a sunrise
+fn terminator_windows(
+    lat: f64,
+    lon: f64,
+    date: NaiveDate,
+    margin: Duration,
+) -> Vec<(DateTime<Utc>, DateTime<Utc>, bool)> {
+    [date.pred_opt(), Some(date), date.succ_opt()]
+        .into_iter()
+        .flatten()
+        .flat_map(|day| {
+            let (sunrise, sunset) = crossings(lat, lon, day, SUNRISE_ALTITUDE);
+            [(sunrise, true), (sunset, false)]
+        })
+        .filter_map(|(time, sunrise)| Some((time? - margin, time? + margin, sunrise)))
+        .collect()
+}
+
+// Overlaps of the gray-line windows, sunrise or sunset plus and minus
+// `margin`, at two positions that start on the given UTC date, in time
+// order. Empty when the windows never meet or the sun doesn't rise or set
+// at one end.
+pub fn gray_line(
+    from: (f64, f64),
+    to: (f64, f64),
+    date: NaiveDate,
+    margin: Duration,
+) -> Vec<GrayLine> {
+    let from = terminator_windows(from.0, from.1, date, margin);
+    let to = terminator_windows(to.0, to.1, date, margin);
+    let mut overlaps = from
+        .iter()
+        .flat_map(|a| to.iter().map(move |b| (a, b)))
+        .filter_map(
+            |(&(a_start, a_end, from_sunrise), &(b_start, b_end, to_sunrise))| {
+                let (start, end) = (a_start.max(b_start), a_end.min(b_end));
+                (start < end && start.date_naive() == date).then_some(GrayLine {
+                    start,
+                    end,
+                    from_sunrise,
+                    to_sunrise,
+                })
+            },
+        )
+        .collect::<Vec<_>>();
+    overlaps.sort_by_key(|overlap| overlap.start);
+    overlaps
+}
@@ -0,0 +1,137 @@
+// Storage for the prefixes of a database. The records live in one vector and
+// the hash table only holds their indexes, hashed by pattern, so every
+// pattern string is allocated once and zone and continent indexes can refer
+// to prefixes by number.
+use crate::{Continent, CqZone, Entity, ItuZone, Overrides, Prefix, UtcOffset};
+use alloc::{sync::Arc, vec::Vec};
+use core::{hash::BuildHasher, ops::Index};
+use hashbrown::{DefaultHashBuilder, HashMap, HashTable};
+
+#[derive(Clone, Default)]
+pub(crate) struct PrefixTable {
+    entries: Vec<Prefix>,
+    index: HashTable<usize>,
+    hasher: DefaultHashBuilder,
+}
+
+impl core::fmt::Debug for PrefixTable {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(&self.entries).finish()
+    }
+}
+
+impl PrefixTable {
+    fn position(&self, pattern: &str) -> Option<usize> {
+        let hash = self.hasher.hash_one(pattern);
+        self.index
+            .find(hash, |&i| self.entries[i].pattern == pattern)
+            .copied()
+    }
+    pub fn get(&self, pattern: &str) -> Option<&Prefix> {
+        self.position(pattern).map(|i| &self.entries[i])
+    }
+    #[cfg(all(test, feature = "fs"))]
+    pub fn get_mut(&mut self, pattern: &str) -> Option<&mut Prefix> {
+        self.position(pattern).map(|i| &mut self.entries[i])
+    }
+    // Add a prefix, returning the one it replaces
+    pub fn insert(&mut self, prefix: Prefix) -> Option<Prefix> {
+        if let Some(i) = self.position(&prefix.pattern) {
+            return Some(core::mem::replace(&mut self.entries[i], prefix));
+        }
+        let hash = self.hasher.hash_one(prefix.pattern.as_str());
+        let (entries, hasher) = (&self.entries, &self.hasher);
+        self.index.insert_unique(hash, entries.len(), |&i| {
+            hasher.hash_one(&entries[i].pattern)
+        });
+        self.entries.push(prefix);
+        None
+    }
+    // Remove a prefix, moving the last one into its place
+    pub fn remove(&mut self, pattern: &str) -> Option<Prefix> {
+        let i = self.position(pattern)?;
+        let hash = self.hasher.hash_one(pattern);
+        self.index.find_entry(hash, |&j| j == i).ok()?.remove();
+        let last = self.entries.len() - 1;
+        if i != last {
+            let hash = self.hasher.hash_one(self.entries[last].pattern.as_str());
+            if let Some(moved) = self.index.find_mut(hash, |&j| j == last) {
+                *moved = i;
+            }
+        }
+        Some(self.entries.swap_remove(i))
+    }
+    pub fn values(&self) -> core::slice::Iter<'_, Prefix> {
+        self.entries.iter()
+    }
+    pub fn values_mut(&mut self) -> core::slice::IterMut<'_, Prefix> {
+        self.entries.iter_mut()
+    }
+    pub fn into_values(self) -> alloc::vec::IntoIter<Prefix> {
+        self.entries.into_iter()
+    }
+    // Indexes of the prefixes in pattern order
+    pub fn sorted(&self) -> Vec<usize> {
+        let mut order = (0..self.entries.len()).collect::<Vec<_>>();
+        order.sort_unstable_by(|&a, &b| self.entries[a].pattern.cmp(&self.entries[b].pattern));
+        order
+    }
+}
+
+impl Index<usize> for PrefixTable {
+    type Output = Prefix;
+    fn index(&self, i: usize) -> &Prefix {
+        &self.entries[i]
+    }
+}
+
+impl Index<&str> for PrefixTable {
+    type Output = Prefix;
+    fn index(&self, pattern: &str) -> &Prefix {
+        self.get(pattern).expect("no such prefix")
+    }
+}
+
+// Entity index and overrides, with coordinates as bits to make them hashable
+type OverridesKey = (
+    usize,
+    Option<CqZone>,
+    Option<ItuZone>,
+    Option<u32>,
+    Option<u32>,
+    Option<Continent>,
+    Option<UtcOffset>,
+);
+
+// Most overridden prefixes repeat the zones of their neighbours, e.g. the
+// many (4)[7] exact calls of the USA, and share one resolved record
+#[derive(Default)]
+pub(crate) struct ResolvedCache {
+    records: HashMap<OverridesKey, Arc<Entity>>,
+}
+
+impl ResolvedCache {
+    pub fn get(
+        &mut self,
+        entity: &Arc<Entity>,
+        entity_index: usize,
+        overrides: &Overrides,
+    ) -> Arc<Entity> {
+        if overrides.is_empty() {
+            return entity.clone();
+        }
+        let key = (
+            entity_index,
+            overrides.cq,
+            overrides.itu,
+            overrides.lat.map(f32::to_bits),
+            overrides.lon.map(f32::to_bits),
+            overrides.continent.clone(),
+            overrides.timezone,
+        );
+        self.records
+            .entry(key)
+            .or_insert_with(|| Arc::new(overrides.apply(entity)))
+            .clone()
+    }
+}
@@ -0,0 +1,107 @@
+// Incremental bookkeeping of the multipliers a contest station has worked
+use crate::{Continent, CqZone, Entity, ItuZone};
+use alloc::{
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+// Multipliers worked on one band
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Multipliers {
+    pub band: String,
+    pub qsos: usize,
+    // Primary prefixes of the entities
+    pub entities: BTreeSet<String>,
+    pub cq_zones: BTreeSet<CqZone>,
+    pub itu_zones: BTreeSet<ItuZone>,
+    pub continents: BTreeSet<Continent>,
+    // Calls the database doesn't resolve
+    pub unresolved: Vec<String>,
+}
+
+// Which multipliers a contact counts as
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NewMultipliers {
+    pub entity: bool,
+    pub cq_zone: bool,
+    pub itu_zone: bool,
+    pub continent: bool,
+}
+
+impl NewMultipliers {
+    pub fn any(&self) -> bool {
+        self.entity || self.cq_zone || self.itu_zone || self.continent
+    }
+}
+
+// Per-band multipliers of a log as it is being written, bands in the order
+// they were first worked
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MultiplierTracker {
+    bands: Vec<Multipliers>,
+}
+
+impl MultiplierTracker {
+    pub fn new() -> MultiplierTracker {
+        MultiplierTracker::default()
+    }
+    fn band_mut(&mut self, band: &str) -> &mut Multipliers {
+        let index = match self.bands.iter().position(|b| b.band == band) {
+            Some(index) => index,
+            None => {
+                self.bands.push(Multipliers {
+                    band: band.to_string(),
+                    ..Multipliers::default()
+                });
+                self.bands.len() - 1
+            }
+        };
+        &mut self.bands[index]
+    }
+    // What a contact with the entity on the band would count as, without
+    // recording it
+    pub fn check(&self, band: &str, entity: &Entity) -> NewMultipliers {
+        let Some(worked) = self.band(band) else {
+            return NewMultipliers {
+                entity: true,
+                cq_zone: true,
+                itu_zone: true,
+                continent: true,
+            };
+        };
+        NewMultipliers {
+            entity: !worked.entities.contains(&entity.prefix),
+            cq_zone: !worked.cq_zones.contains(&entity.cq),
+            itu_zone: !worked.itu_zones.contains(&entity.itu),
+            continent: !worked.continents.contains(&entity.continent),
+        }
+    }
+    // Record a contact with a resolved call, returning what was new
+    pub fn add(&mut self, band: &str, entity: &Entity) -> NewMultipliers {
+        let new = self.check(band, entity);
+        let worked = self.band_mut(band);
+        worked.qsos += 1;
+        worked.entities.insert(entity.prefix.clone());
+        worked.cq_zones.insert(entity.cq);
+        worked.itu_zones.insert(entity.itu);
+        worked.continents.insert(entity.continent.clone());
+        new
+    }
+    // Record a contact with a call the database doesn't resolve
+    pub fn add_unresolved(&mut self, band: &str, callsign: &str) {
+        let worked = self.band_mut(band);
+        worked.qsos += 1;
+        worked.unresolved.push(callsign.to_string());
+    }
+    pub fn band(&self, band: &str) -> Option<&Multipliers> {
+        self.bands.iter().find(|b| b.band == band)
+    }
+    pub fn bands(&self) -> impl Iterator<Item = &Multipliers> {
+        self.bands.iter()
+    }
+    pub fn into_bands(self) -> Vec<Multipliers> {
+        self.bands
+    }
+}
@@ -0,0 +1,109 @@
+// Consistency checks of a loaded database
+use crate::{Cty, Entity};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Issue {
+    // Pattern defined for more than one entity (in file order), the last one wins
+    ConflictingPrefix {
+        pattern: String,
+        entities: Vec<String>,
+    },
+    // Continent other than the seven of cty.dat, kept by lenient parsing
+    UnknownContinent {
+        pattern: String,
+        continent: String,
+    },
+    // Coordinates of exactly 0/0, usually a missing value
+    ZeroCoordinates {
+        pattern: String,
+    },
+    // Exact call replaced by a plain prefix of the same pattern, which then
+    // also matches every longer call starting with it
+    ShadowedExactCall {
+        pattern: String,
+    },
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Issue::ConflictingPrefix { pattern, entities } => {
+                write!(f, "{pattern} maps to {}", entities.join(", "))
+            }
+            Issue::UnknownContinent { pattern, continent } => {
+                write!(f, "{pattern} has unknown continent {continent}")
+            }
+            Issue::ZeroCoordinates { pattern } => write!(f, "{pattern} has coordinates 0/0"),
+            Issue::ShadowedExactCall { pattern } => {
+                write!(f, "Exact call {pattern} is shadowed by a prefix")
+            }
+        }
+    }
+}
+
+fn check_entity(pattern: &str, entity: &Entity, issues: &mut Vec<Issue>) {
+    if !entity.continent.is_known() {
+        issues.push(Issue::UnknownContinent {
+            pattern: pattern.to_string(),
+            continent: entity.continent.to_string(),
+        });
+    }
+    if entity.lat == 0.0 && entity.lon == 0.0 {
+        issues.push(Issue::ZeroCoordinates {
+            pattern: pattern.to_string(),
+        });
+    }
+}
+
+impl Cty {
+    // Report suspicious data, sorted by pattern. Calls that WAE-only entities
+    // share with their DXCC entity are not reported as conflicts.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        for entity in self.entities() {
+            check_entity(&entity.prefix, entity, &mut issues);
+        }
+        let mut prefixes = self.prefixes().collect::<Vec<_>>();
+        prefixes.sort_by(|a, b| a.pattern.cmp(&b.pattern));
+        for prefix in prefixes.iter().filter(|p| !p.overrides.is_empty()) {
+            check_entity(&prefix.pattern, prefix.resolved(), &mut issues);
+        }
+        for prefix in prefixes {
+            let shadowed = self
+                .replaced_by_edit(&prefix.pattern)
+                .into_iter()
+                .chain(self.shadowed.iter().filter(|s| s.pattern == prefix.pattern))
+                .collect::<Vec<_>>();
+            if shadowed.iter().any(|s| s.is_exact) && !prefix.is_exact {
+                issues.push(Issue::ShadowedExactCall {
+                    pattern: prefix.pattern.clone(),
+                });
+            }
+            let mut entities = shadowed
+                .iter()
+                .chain([&prefix])
+                .filter(|p| !p.entity.waedc)
+                .map(|p| p.entity.name.clone())
+                .collect::<Vec<_>>();
+            entities.dedup();
+            if entities.len() > 1 {
+                issues.push(Issue::ConflictingPrefix {
+                    pattern: prefix.pattern.clone(),
+                    entities,
+                });
+            }
+        }
+        issues.sort_by_key(|issue| match issue {
+            Issue::ConflictingPrefix { pattern, .. }
+            | Issue::UnknownContinent { pattern, .. }
+            | Issue::ZeroCoordinates { pattern }
+            | Issue::ShadowedExactCall { pattern } => pattern.clone(),
+        });
+        issues
+    }
+}
@@ -0,0 +1,46 @@
+// Release of the country file, from its =VERyyyymmdd exact call
+use crate::Cty;
+use alloc::string::{String, ToString};
+
+// Digits of a release marker, e.g. 20231103 of VER20231103
+fn release(pattern: &str) -> Option<&str> {
+    let digits = pattern.strip_prefix("VER")?;
+    (digits.len() == 8 && digits.bytes().all(|b| b.is_ascii_digit())).then_some(digits)
+}
+
+impl Cty {
+    // The latest release marker, merged files can carry several
+    pub(crate) fn find_version(&self) -> Option<String> {
+        self.prefixes()
+            .filter(|p| p.is_exact)
+            .filter_map(|p| release(&p.pattern))
+            .max()
+            .map(str::to_string)
+    }
+    // Release of the loaded country file as yyyymmdd, e.g. "20231103"
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+    #[cfg(feature = "timezone")]
+    pub fn release_date(&self) -> Option<chrono::NaiveDate> {
+        chrono::NaiveDate::parse_from_str(self.version()?, "%Y%m%d").ok()
+    }
+    // Time since the release date by the system clock, zero for releases
+    // dated in the future
+    #[cfg(all(feature = "std", feature = "timezone"))]
+    pub fn age(&self) -> Option<std::time::Duration> {
+        let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1)?;
+        let days = self.release_date()?.signed_duration_since(epoch).num_days();
+        let released = std::time::Duration::from_secs(u64::try_from(days).ok()? * 86_400);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?;
+        Some(now.saturating_sub(released))
+    }
+    // Whether the file is older than `max_age`, files without a release
+    // marker count as outdated
+    #[cfg(all(feature = "std", feature = "timezone"))]
+    pub fn is_outdated(&self, max_age: std::time::Duration) -> bool {
+        self.age().is_none_or(|age| age > max_age)
+    }
+}
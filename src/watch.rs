@@ -0,0 +1,83 @@
+// Hot reloading of a country file that changes on disk
+use crate::{Cty, CtyBuilder, CtyService, Entity};
+use arc_swap::ArcSwap;
+use std::{
+    error::Error,
+    fs,
+    sync::{Arc, Weak},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+// Handle to a database that is reparsed and swapped in atomically whenever
+// its files change. Clones share the same database, the watcher thread stops
+// once every handle is dropped.
+#[derive(Clone)]
+pub struct WatchedCty {
+    service: CtyService,
+}
+
+// Latest modification time of any of the files
+fn modified(files: &[String]) -> Option<SystemTime> {
+    files
+        .iter()
+        .map(|f| fs::metadata(f).and_then(|m| m.modified()).ok())
+        .max()
+        .flatten()
+}
+
+impl WatchedCty {
+    // Watch a single file, checking for changes every `interval`
+    pub fn new(filename: &str, interval: Duration) -> Result<WatchedCty, Box<dyn Error>> {
+        WatchedCty::with_builder(CtyBuilder::new(), filename, interval)
+    }
+
+    // Watch a file and the builder's overlay files, reloading with its configuration
+    pub fn with_builder(
+        builder: CtyBuilder,
+        filename: &str,
+        interval: Duration,
+    ) -> Result<WatchedCty, Box<dyn Error>> {
+        let mut files = vec![filename.to_string()];
+        files.extend(builder.files.iter().cloned());
+        let mut last_modified = modified(&files);
+        let (cty, _) = builder.load(filename)?;
+        let watched = WatchedCty {
+            service: CtyService::new(cty),
+        };
+
+        let weak: Weak<ArcSwap<Cty>> = Arc::downgrade(&watched.service.cty);
+        let filename = filename.to_string();
+        thread::spawn(move || loop {
+            thread::sleep(interval);
+            let Some(cty) = weak.upgrade() else {
+                break;
+            };
+            let modified = modified(&files);
+            if modified == last_modified {
+                continue;
+            }
+            // A failed reload keeps the previous data until the files change again
+            if let Ok((reloaded, _)) = builder.load(&filename) {
+                cty.store(Arc::new(reloaded));
+            }
+            last_modified = modified;
+        });
+        Ok(watched)
+    }
+
+    // Snapshot of the current database
+    pub fn current(&self) -> Arc<Cty> {
+        self.service.current()
+    }
+
+    pub fn lookup(&self, callsign: &str) -> Option<Arc<Entity>> {
+        self.service.lookup(callsign)
+    }
+
+    // The underlying service, e.g. to hand out to code that also serves
+    // databases loaded some other way
+    pub fn service(&self) -> &CtyService {
+        &self.service
+    }
+}
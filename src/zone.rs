@@ -0,0 +1,74 @@
+// CQ and ITU zone numbers, checked against their range when parsed so the two
+// kinds can't be mixed up or hold values no zone map has
+use alloc::{boxed::Box, format};
+use core::{error::Error, fmt, num::NonZeroU8, str::FromStr};
+
+macro_rules! zone {
+    ($name:ident, $kind:literal, $max:literal) => {
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(NonZeroU8);
+
+        impl $name {
+            pub const MAX: u8 = $max;
+
+            pub fn new(zone: u32) -> Option<$name> {
+                match zone {
+                    1..=$max => NonZeroU8::new(zone as u8).map($name),
+                    _ => None,
+                }
+            }
+            pub fn get(self) -> u8 {
+                self.0.get()
+            }
+            // Every zone from 1 up
+            pub fn all() -> impl Iterator<Item = $name> {
+                (1..=$max).filter_map($name::new)
+            }
+        }
+
+        impl TryFrom<u32> for $name {
+            type Error = Box<dyn Error>;
+
+            fn try_from(zone: u32) -> Result<Self, Self::Error> {
+                $name::new(zone)
+                    .ok_or_else(|| format!("{} zone {zone} out of range 1-{}", $kind, $max).into())
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Box<dyn Error>;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                s.parse::<u32>()?.try_into()
+            }
+        }
+
+        impl PartialEq<u32> for $name {
+            fn eq(&self, other: &u32) -> bool {
+                u32::from(self.get()) == *other
+            }
+        }
+
+        impl From<$name> for u32 {
+            fn from(zone: $name) -> u32 {
+                zone.get().into()
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                self.0.fmt(f)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_u8(self.get())
+            }
+        }
+    };
+}
+
+zone!(CqZone, "CQ", 40);
+zone!(ItuZone, "ITU", 90);